@@ -1,8 +1,11 @@
 #[allow(unaligned_references)]
-use super::decimal::SwitchboardDecimal;
+use super::decimal::{BorshDecimal, SwitchboardDecimal};
 use super::error::SwitchboardError;
+use super::instruction::{build_instruction, invoke_instruction};
 use anchor_lang::prelude::*;
+use anchor_spl::token::TokenAccount;
 use bytemuck::{Pod, Zeroable};
+use solana_program::instruction::Instruction;
 use solana_program::pubkey::Pubkey;
 use std::cell::Ref;
 
@@ -117,9 +120,184 @@ impl AggregatorAccountData {
 unsafe impl Pod for AggregatorAccountData {}
 unsafe impl Zeroable for AggregatorAccountData {}
 
+#[derive(Clone, Debug, AnchorSerialize, AnchorDeserialize)]
+pub struct AggregatorInitParams {
+    pub name: [u8; 32],
+    pub metadata: [u8; 128],
+    pub batch_size: u32,
+    pub min_oracle_results: u32,
+    pub min_job_results: u32,
+    pub min_update_delay_seconds: u32,
+    pub start_after: i64,
+    pub variance_threshold: BorshDecimal,
+    pub force_report_period: i64,
+    pub expiration: i64,
+    pub state_bump: u8,
+}
+
+#[derive(Clone, Debug, AnchorSerialize, AnchorDeserialize)]
+pub struct JobInitParams {
+    pub name: [u8; 32],
+    pub expiration: i64,
+    pub state_bump: u8,
+    pub data: Vec<u8>,
+}
+
+#[derive(Clone, Debug, AnchorSerialize, AnchorDeserialize)]
+pub struct AggregatorSaveResultParams {
+    pub oracle_idx: u32,
+    pub error: bool,
+    pub value: BorshDecimal,
+    pub jobs_checksum: [u8; 32],
+    pub min_response: BorshDecimal,
+    pub max_response: BorshDecimal,
+    pub feed_permission_bump: u8,
+    pub oracle_permission_bump: u8,
+    pub lease_bump: u8,
+    pub state_bump: u8,
+}
+
+#[derive(Accounts)]
+#[instruction(params: AggregatorOpenRoundParams)] // rpc parameters hint
+pub struct AggregatorOpenRound<'info> {
+    #[account(mut)]
+    pub aggregator: AccountInfo<'info>,
+    #[account(
+        mut,
+        seeds = [
+            b"LeaseAccountData",
+            oracle_queue.key().as_ref(),
+            aggregator.key().as_ref()
+        ],
+        bump = params.lease_bump
+    )]
+    pub lease: AccountInfo<'info>,
+    #[account(mut)]
+    pub oracle_queue: AccountInfo<'info>,
+    pub queue_authority: AccountInfo<'info>,
+    #[account(
+        mut,
+        seeds = [
+            b"PermissionAccountData",
+            queue_authority.key().as_ref(),
+            oracle_queue.key().as_ref(),
+            aggregator.key().as_ref()
+        ],
+        bump = params.permission_bump
+    )]
+    pub permission: AccountInfo<'info>,
+    #[account(mut, constraint = escrow.owner == program_state.key())]
+    pub escrow: Account<'info, TokenAccount>,
+    #[account(seeds = [b"STATE"], bump = params.state_bump)]
+    pub program_state: AccountInfo<'info>,
+    // Token account rewarded for opening the round.
+    #[account(mut)]
+    pub payout_wallet: Account<'info, TokenAccount>,
+    pub token_program: AccountInfo<'info>,
+    pub data_buffer: AccountInfo<'info>,
+    pub mint: AccountInfo<'info>,
+}
+
+#[derive(Clone, Debug, AnchorSerialize, AnchorDeserialize)]
+pub struct AggregatorOpenRoundParams {
+    pub state_bump: u8,
+    pub lease_bump: u8,
+    pub permission_bump: u8,
+    pub jitter: u8,
+}
+
+impl<'info> AggregatorOpenRound<'info> {
+    pub(crate) fn discriminator() -> [u8; 8] {
+        [239, 69, 229, 179, 156, 246, 118, 191]
+    }
+
+    pub fn get_instruction(
+        &self,
+        program_id: Pubkey,
+        params: AggregatorOpenRoundParams,
+    ) -> anchor_lang::Result<Instruction> {
+        build_instruction(
+            program_id,
+            AggregatorOpenRound::discriminator(),
+            params,
+            self.to_account_metas(None),
+        )
+    }
+
+    pub fn invoke(
+        &self,
+        program: AccountInfo<'info>,
+        state_bump: u8,
+        lease_bump: u8,
+        permission_bump: u8,
+        jitter: u8,
+    ) -> anchor_lang::Result<()> {
+        let cpi_params = AggregatorOpenRoundParams {
+            state_bump,
+            lease_bump,
+            permission_bump,
+            jitter,
+        };
+        let instruction = self.get_instruction(*program.key, cpi_params)?;
+        let account_infos = self.to_account_infos();
+
+        invoke_instruction(&instruction, &account_infos, None)
+    }
+
+    pub fn invoke_signed(
+        &self,
+        program: AccountInfo<'info>,
+        state_bump: u8,
+        lease_bump: u8,
+        permission_bump: u8,
+        jitter: u8,
+        signer_seeds: &[&[&[u8]]],
+    ) -> anchor_lang::Result<()> {
+        let cpi_params = AggregatorOpenRoundParams {
+            state_bump,
+            lease_bump,
+            permission_bump,
+            jitter,
+        };
+        let instruction = self.get_instruction(*program.key, cpi_params)?;
+        let account_infos = self.to_account_infos();
+
+        invoke_instruction(&instruction, &account_infos, Some(signer_seeds))
+    }
+
+    fn to_account_infos(&self) -> Vec<AccountInfo<'info>> {
+        vec![
+            self.aggregator.clone(),
+            self.lease.clone(),
+            self.oracle_queue.clone(),
+            self.queue_authority.clone(),
+            self.permission.clone(),
+            self.escrow.to_account_info(),
+            self.program_state.clone(),
+            self.payout_wallet.to_account_info(),
+            self.token_program.clone(),
+            self.data_buffer.clone(),
+            self.mint.clone(),
+        ]
+    }
+
+    #[allow(unused_variables)]
+    fn to_account_metas(&self, is_signer: Option<bool>) -> Vec<AccountMeta> {
+        self.to_account_infos()
+            .iter()
+            .map(|account| AccountMeta {
+                pubkey: *account.key,
+                is_signer: account.is_signer,
+                is_writable: account.is_writable,
+            })
+            .collect()
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::instruction::tests::TestAccounts;
     impl<'info> Default for AggregatorAccountData {
         fn default() -> Self {
             unsafe { std::mem::zeroed() }
@@ -174,4 +352,50 @@ mod tests {
             "Aggregator is not currently populated with a valid round."
         );
     }
+
+    #[test]
+    fn test_aggregator_open_round_instruction() {
+        // escrow and payout wallet are token accounts
+        let mut test_accounts = TestAccounts::new(11, &[5, 7], 6);
+        let keys = test_accounts.keys.clone();
+        let infos = test_accounts.infos(&[3]);
+        let accounts = AggregatorOpenRound {
+            aggregator: infos[0].clone(),
+            lease: infos[1].clone(),
+            oracle_queue: infos[2].clone(),
+            queue_authority: infos[3].clone(),
+            permission: infos[4].clone(),
+            escrow: Account::try_from(&infos[5]).unwrap(),
+            program_state: infos[6].clone(),
+            payout_wallet: Account::try_from(&infos[7]).unwrap(),
+            token_program: infos[8].clone(),
+            data_buffer: infos[9].clone(),
+            mint: infos[10].clone(),
+        };
+
+        let ix = accounts
+            .get_instruction(
+                crate::SWITCHBOARD_V2_DEVNET,
+                AggregatorOpenRoundParams {
+                    state_bump: 1,
+                    lease_bump: 2,
+                    permission_bump: 3,
+                    jitter: 4,
+                },
+            )
+            .unwrap();
+        assert_eq!(
+            ix.data,
+            vec![239, 69, 229, 179, 156, 246, 118, 191, 1, 2, 3, 4]
+        );
+        assert_eq!(
+            ix.accounts
+                .iter()
+                .map(|meta| meta.pubkey)
+                .collect::<Vec<_>>(),
+            keys
+        );
+        assert!(!ix.accounts[3].is_writable);
+        assert!(ix.accounts[0].is_writable);
+    }
 }
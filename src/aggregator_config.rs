@@ -0,0 +1,670 @@
+//! Authority operations on an aggregator's configuration, for programs that own feeds through a
+//! PDA authority.
+use super::decimal::BorshDecimal;
+use super::instruction::{build_instruction, invoke_instruction};
+use anchor_lang::prelude::*;
+use solana_program::instruction::Instruction;
+use solana_program::pubkey::Pubkey;
+
+/// CPI to set the minimum change in the feed value before a new result is reported.
+#[derive(Accounts)]
+pub struct AggregatorSetVarianceThreshold<'info> {
+    #[account(mut)]
+    pub aggregator: AccountInfo<'info>,
+    #[account(signer)]
+    pub authority: AccountInfo<'info>,
+}
+
+#[derive(Clone, Debug, AnchorSerialize, AnchorDeserialize)]
+pub struct AggregatorSetVarianceThresholdParams {
+    pub variance_threshold: BorshDecimal,
+}
+
+impl<'info> AggregatorSetVarianceThreshold<'info> {
+    pub(crate) fn discriminator() -> [u8; 8] {
+        [212, 41, 238, 231, 119, 125, 150, 6]
+    }
+
+    pub fn get_instruction(
+        &self,
+        program_id: Pubkey,
+        params: AggregatorSetVarianceThresholdParams,
+    ) -> anchor_lang::Result<Instruction> {
+        build_instruction(
+            program_id,
+            AggregatorSetVarianceThreshold::discriminator(),
+            params,
+            self.to_account_metas(),
+        )
+    }
+
+    pub fn invoke(
+        &self,
+        program: AccountInfo<'info>,
+        variance_threshold: BorshDecimal,
+    ) -> anchor_lang::Result<()> {
+        let instruction = self.get_instruction(
+            *program.key,
+            AggregatorSetVarianceThresholdParams { variance_threshold },
+        )?;
+        invoke_instruction(&instruction, &self.to_account_infos(), None)
+    }
+
+    pub fn invoke_signed(
+        &self,
+        program: AccountInfo<'info>,
+        variance_threshold: BorshDecimal,
+        signer_seeds: &[&[&[u8]]],
+    ) -> anchor_lang::Result<()> {
+        let instruction = self.get_instruction(
+            *program.key,
+            AggregatorSetVarianceThresholdParams { variance_threshold },
+        )?;
+        invoke_instruction(&instruction, &self.to_account_infos(), Some(signer_seeds))
+    }
+
+    fn to_account_infos(&self) -> Vec<AccountInfo<'info>> {
+        vec![self.aggregator.clone(), self.authority.clone()]
+    }
+
+    fn to_account_metas(&self) -> Vec<AccountMeta> {
+        authority_account_metas(&self.aggregator, &self.authority, None)
+    }
+}
+
+/// CPI to set the number of job results each oracle needs for a valid response.
+#[derive(Accounts)]
+pub struct AggregatorSetMinJobs<'info> {
+    #[account(mut)]
+    pub aggregator: AccountInfo<'info>,
+    #[account(signer)]
+    pub authority: AccountInfo<'info>,
+}
+
+#[derive(Clone, Debug, AnchorSerialize, AnchorDeserialize)]
+pub struct AggregatorSetMinJobsParams {
+    pub min_job_results: u32,
+}
+
+impl<'info> AggregatorSetMinJobs<'info> {
+    pub(crate) fn discriminator() -> [u8; 8] {
+        [158, 77, 149, 156, 157, 42, 25, 16]
+    }
+
+    pub fn get_instruction(
+        &self,
+        program_id: Pubkey,
+        params: AggregatorSetMinJobsParams,
+    ) -> anchor_lang::Result<Instruction> {
+        build_instruction(
+            program_id,
+            AggregatorSetMinJobs::discriminator(),
+            params,
+            self.to_account_metas(),
+        )
+    }
+
+    pub fn invoke(
+        &self,
+        program: AccountInfo<'info>,
+        min_job_results: u32,
+    ) -> anchor_lang::Result<()> {
+        let instruction =
+            self.get_instruction(*program.key, AggregatorSetMinJobsParams { min_job_results })?;
+        invoke_instruction(&instruction, &self.to_account_infos(), None)
+    }
+
+    pub fn invoke_signed(
+        &self,
+        program: AccountInfo<'info>,
+        min_job_results: u32,
+        signer_seeds: &[&[&[u8]]],
+    ) -> anchor_lang::Result<()> {
+        let instruction =
+            self.get_instruction(*program.key, AggregatorSetMinJobsParams { min_job_results })?;
+        invoke_instruction(&instruction, &self.to_account_infos(), Some(signer_seeds))
+    }
+
+    fn to_account_infos(&self) -> Vec<AccountInfo<'info>> {
+        vec![self.aggregator.clone(), self.authority.clone()]
+    }
+
+    fn to_account_metas(&self) -> Vec<AccountMeta> {
+        authority_account_metas(&self.aggregator, &self.authority, None)
+    }
+}
+
+/// CPI to set the number of oracle responses a round needs to be accepted.
+#[derive(Accounts)]
+pub struct AggregatorSetMinOracles<'info> {
+    #[account(mut)]
+    pub aggregator: AccountInfo<'info>,
+    #[account(signer)]
+    pub authority: AccountInfo<'info>,
+}
+
+#[derive(Clone, Debug, AnchorSerialize, AnchorDeserialize)]
+pub struct AggregatorSetMinOraclesParams {
+    pub min_oracle_results: u32,
+}
+
+impl<'info> AggregatorSetMinOracles<'info> {
+    pub(crate) fn discriminator() -> [u8; 8] {
+        [178, 35, 71, 65, 153, 193, 145, 28]
+    }
+
+    pub fn get_instruction(
+        &self,
+        program_id: Pubkey,
+        params: AggregatorSetMinOraclesParams,
+    ) -> anchor_lang::Result<Instruction> {
+        build_instruction(
+            program_id,
+            AggregatorSetMinOracles::discriminator(),
+            params,
+            self.to_account_metas(),
+        )
+    }
+
+    pub fn invoke(
+        &self,
+        program: AccountInfo<'info>,
+        min_oracle_results: u32,
+    ) -> anchor_lang::Result<()> {
+        let instruction = self.get_instruction(
+            *program.key,
+            AggregatorSetMinOraclesParams { min_oracle_results },
+        )?;
+        invoke_instruction(&instruction, &self.to_account_infos(), None)
+    }
+
+    pub fn invoke_signed(
+        &self,
+        program: AccountInfo<'info>,
+        min_oracle_results: u32,
+        signer_seeds: &[&[&[u8]]],
+    ) -> anchor_lang::Result<()> {
+        let instruction = self.get_instruction(
+            *program.key,
+            AggregatorSetMinOraclesParams { min_oracle_results },
+        )?;
+        invoke_instruction(&instruction, &self.to_account_infos(), Some(signer_seeds))
+    }
+
+    fn to_account_infos(&self) -> Vec<AccountInfo<'info>> {
+        vec![self.aggregator.clone(), self.authority.clone()]
+    }
+
+    fn to_account_metas(&self) -> Vec<AccountMeta> {
+        authority_account_metas(&self.aggregator, &self.authority, None)
+    }
+}
+
+/// CPI to set the number of oracles assigned to each round.
+#[derive(Accounts)]
+pub struct AggregatorSetBatchSize<'info> {
+    #[account(mut)]
+    pub aggregator: AccountInfo<'info>,
+    #[account(signer)]
+    pub authority: AccountInfo<'info>,
+}
+
+#[derive(Clone, Debug, AnchorSerialize, AnchorDeserialize)]
+pub struct AggregatorSetBatchSizeParams {
+    pub batch_size: u32,
+}
+
+impl<'info> AggregatorSetBatchSize<'info> {
+    pub(crate) fn discriminator() -> [u8; 8] {
+        [170, 87, 187, 247, 181, 156, 143, 86]
+    }
+
+    pub fn get_instruction(
+        &self,
+        program_id: Pubkey,
+        params: AggregatorSetBatchSizeParams,
+    ) -> anchor_lang::Result<Instruction> {
+        build_instruction(
+            program_id,
+            AggregatorSetBatchSize::discriminator(),
+            params,
+            self.to_account_metas(),
+        )
+    }
+
+    pub fn invoke(&self, program: AccountInfo<'info>, batch_size: u32) -> anchor_lang::Result<()> {
+        let instruction =
+            self.get_instruction(*program.key, AggregatorSetBatchSizeParams { batch_size })?;
+        invoke_instruction(&instruction, &self.to_account_infos(), None)
+    }
+
+    pub fn invoke_signed(
+        &self,
+        program: AccountInfo<'info>,
+        batch_size: u32,
+        signer_seeds: &[&[&[u8]]],
+    ) -> anchor_lang::Result<()> {
+        let instruction =
+            self.get_instruction(*program.key, AggregatorSetBatchSizeParams { batch_size })?;
+        invoke_instruction(&instruction, &self.to_account_infos(), Some(signer_seeds))
+    }
+
+    fn to_account_infos(&self) -> Vec<AccountInfo<'info>> {
+        vec![self.aggregator.clone(), self.authority.clone()]
+    }
+
+    fn to_account_metas(&self) -> Vec<AccountMeta> {
+        authority_account_metas(&self.aggregator, &self.authority, None)
+    }
+}
+
+/// CPI to set the minimum number of seconds between rounds.
+#[derive(Accounts)]
+pub struct AggregatorSetUpdateInterval<'info> {
+    #[account(mut)]
+    pub aggregator: AccountInfo<'info>,
+    #[account(signer)]
+    pub authority: AccountInfo<'info>,
+}
+
+#[derive(Clone, Debug, AnchorSerialize, AnchorDeserialize)]
+pub struct AggregatorSetUpdateIntervalParams {
+    pub new_interval: u32,
+}
+
+impl<'info> AggregatorSetUpdateInterval<'info> {
+    pub(crate) fn discriminator() -> [u8; 8] {
+        [179, 12, 13, 144, 219, 88, 81, 104]
+    }
+
+    pub fn get_instruction(
+        &self,
+        program_id: Pubkey,
+        params: AggregatorSetUpdateIntervalParams,
+    ) -> anchor_lang::Result<Instruction> {
+        build_instruction(
+            program_id,
+            AggregatorSetUpdateInterval::discriminator(),
+            params,
+            self.to_account_metas(),
+        )
+    }
+
+    pub fn invoke(
+        &self,
+        program: AccountInfo<'info>,
+        new_interval: u32,
+    ) -> anchor_lang::Result<()> {
+        let instruction = self.get_instruction(
+            *program.key,
+            AggregatorSetUpdateIntervalParams { new_interval },
+        )?;
+        invoke_instruction(&instruction, &self.to_account_infos(), None)
+    }
+
+    pub fn invoke_signed(
+        &self,
+        program: AccountInfo<'info>,
+        new_interval: u32,
+        signer_seeds: &[&[&[u8]]],
+    ) -> anchor_lang::Result<()> {
+        let instruction = self.get_instruction(
+            *program.key,
+            AggregatorSetUpdateIntervalParams { new_interval },
+        )?;
+        invoke_instruction(&instruction, &self.to_account_infos(), Some(signer_seeds))
+    }
+
+    fn to_account_infos(&self) -> Vec<AccountInfo<'info>> {
+        vec![self.aggregator.clone(), self.authority.clone()]
+    }
+
+    fn to_account_metas(&self) -> Vec<AccountMeta> {
+        authority_account_metas(&self.aggregator, &self.authority, None)
+    }
+}
+
+/// CPI to add a job to the aggregator, optionally weighting its result.
+#[derive(Accounts)]
+pub struct AggregatorAddJob<'info> {
+    #[account(mut)]
+    pub aggregator: AccountInfo<'info>,
+    #[account(signer)]
+    pub authority: AccountInfo<'info>,
+    #[account(mut)]
+    pub job: AccountInfo<'info>,
+}
+
+#[derive(Clone, Debug, AnchorSerialize, AnchorDeserialize)]
+pub struct AggregatorAddJobParams {
+    pub weight: Option<u8>,
+}
+
+impl<'info> AggregatorAddJob<'info> {
+    pub(crate) fn discriminator() -> [u8; 8] {
+        [132, 30, 35, 51, 115, 142, 186, 10]
+    }
+
+    pub fn get_instruction(
+        &self,
+        program_id: Pubkey,
+        params: AggregatorAddJobParams,
+    ) -> anchor_lang::Result<Instruction> {
+        build_instruction(
+            program_id,
+            AggregatorAddJob::discriminator(),
+            params,
+            self.to_account_metas(),
+        )
+    }
+
+    pub fn invoke(
+        &self,
+        program: AccountInfo<'info>,
+        weight: Option<u8>,
+    ) -> anchor_lang::Result<()> {
+        let instruction = self.get_instruction(*program.key, AggregatorAddJobParams { weight })?;
+        invoke_instruction(&instruction, &self.to_account_infos(), None)
+    }
+
+    pub fn invoke_signed(
+        &self,
+        program: AccountInfo<'info>,
+        weight: Option<u8>,
+        signer_seeds: &[&[&[u8]]],
+    ) -> anchor_lang::Result<()> {
+        let instruction = self.get_instruction(*program.key, AggregatorAddJobParams { weight })?;
+        invoke_instruction(&instruction, &self.to_account_infos(), Some(signer_seeds))
+    }
+
+    fn to_account_infos(&self) -> Vec<AccountInfo<'info>> {
+        vec![
+            self.aggregator.clone(),
+            self.authority.clone(),
+            self.job.clone(),
+        ]
+    }
+
+    fn to_account_metas(&self) -> Vec<AccountMeta> {
+        authority_account_metas(&self.aggregator, &self.authority, Some(&self.job))
+    }
+}
+
+/// CPI to remove the job at `job_idx` in the aggregator's job list.
+#[derive(Accounts)]
+pub struct AggregatorRemoveJob<'info> {
+    #[account(mut)]
+    pub aggregator: AccountInfo<'info>,
+    #[account(signer)]
+    pub authority: AccountInfo<'info>,
+    #[account(mut)]
+    pub job: AccountInfo<'info>,
+}
+
+#[derive(Clone, Debug, AnchorSerialize, AnchorDeserialize)]
+pub struct AggregatorRemoveJobParams {
+    pub job_idx: u32,
+}
+
+impl<'info> AggregatorRemoveJob<'info> {
+    pub(crate) fn discriminator() -> [u8; 8] {
+        [158, 221, 231, 65, 41, 151, 155, 172]
+    }
+
+    pub fn get_instruction(
+        &self,
+        program_id: Pubkey,
+        params: AggregatorRemoveJobParams,
+    ) -> anchor_lang::Result<Instruction> {
+        build_instruction(
+            program_id,
+            AggregatorRemoveJob::discriminator(),
+            params,
+            self.to_account_metas(),
+        )
+    }
+
+    pub fn invoke(&self, program: AccountInfo<'info>, job_idx: u32) -> anchor_lang::Result<()> {
+        let instruction =
+            self.get_instruction(*program.key, AggregatorRemoveJobParams { job_idx })?;
+        invoke_instruction(&instruction, &self.to_account_infos(), None)
+    }
+
+    pub fn invoke_signed(
+        &self,
+        program: AccountInfo<'info>,
+        job_idx: u32,
+        signer_seeds: &[&[&[u8]]],
+    ) -> anchor_lang::Result<()> {
+        let instruction =
+            self.get_instruction(*program.key, AggregatorRemoveJobParams { job_idx })?;
+        invoke_instruction(&instruction, &self.to_account_infos(), Some(signer_seeds))
+    }
+
+    fn to_account_infos(&self) -> Vec<AccountInfo<'info>> {
+        vec![
+            self.aggregator.clone(),
+            self.authority.clone(),
+            self.job.clone(),
+        ]
+    }
+
+    fn to_account_metas(&self) -> Vec<AccountMeta> {
+        authority_account_metas(&self.aggregator, &self.authority, Some(&self.job))
+    }
+}
+
+/// CPI to transfer the aggregator's authority to `new_authority`.
+#[derive(Accounts)]
+pub struct AggregatorSetAuthority<'info> {
+    #[account(mut)]
+    pub aggregator: AccountInfo<'info>,
+    #[account(signer)]
+    pub authority: AccountInfo<'info>,
+    pub new_authority: AccountInfo<'info>,
+}
+
+#[derive(Clone, Debug, AnchorSerialize, AnchorDeserialize)]
+pub struct AggregatorSetAuthorityParams {}
+
+impl<'info> AggregatorSetAuthority<'info> {
+    pub(crate) fn discriminator() -> [u8; 8] {
+        [140, 176, 3, 173, 23, 2, 4, 81]
+    }
+
+    pub fn get_instruction(
+        &self,
+        program_id: Pubkey,
+        params: AggregatorSetAuthorityParams,
+    ) -> anchor_lang::Result<Instruction> {
+        build_instruction(
+            program_id,
+            AggregatorSetAuthority::discriminator(),
+            params,
+            self.to_account_metas(),
+        )
+    }
+
+    pub fn invoke(&self, program: AccountInfo<'info>) -> anchor_lang::Result<()> {
+        let instruction = self.get_instruction(*program.key, AggregatorSetAuthorityParams {})?;
+        invoke_instruction(&instruction, &self.to_account_infos(), None)
+    }
+
+    pub fn invoke_signed(
+        &self,
+        program: AccountInfo<'info>,
+        signer_seeds: &[&[&[u8]]],
+    ) -> anchor_lang::Result<()> {
+        let instruction = self.get_instruction(*program.key, AggregatorSetAuthorityParams {})?;
+        invoke_instruction(&instruction, &self.to_account_infos(), Some(signer_seeds))
+    }
+
+    fn to_account_infos(&self) -> Vec<AccountInfo<'info>> {
+        vec![
+            self.aggregator.clone(),
+            self.authority.clone(),
+            self.new_authority.clone(),
+        ]
+    }
+
+    fn to_account_metas(&self) -> Vec<AccountMeta> {
+        authority_account_metas(&self.aggregator, &self.authority, Some(&self.new_authority))
+    }
+}
+
+/// CPI to permanently lock the aggregator's configuration.
+#[derive(Accounts)]
+pub struct AggregatorLock<'info> {
+    #[account(mut)]
+    pub aggregator: AccountInfo<'info>,
+    #[account(signer)]
+    pub authority: AccountInfo<'info>,
+}
+
+#[derive(Clone, Debug, AnchorSerialize, AnchorDeserialize)]
+pub struct AggregatorLockParams {}
+
+impl<'info> AggregatorLock<'info> {
+    pub(crate) fn discriminator() -> [u8; 8] {
+        [228, 238, 67, 53, 69, 176, 185, 227]
+    }
+
+    pub fn get_instruction(
+        &self,
+        program_id: Pubkey,
+        params: AggregatorLockParams,
+    ) -> anchor_lang::Result<Instruction> {
+        build_instruction(
+            program_id,
+            AggregatorLock::discriminator(),
+            params,
+            self.to_account_metas(),
+        )
+    }
+
+    pub fn invoke(&self, program: AccountInfo<'info>) -> anchor_lang::Result<()> {
+        let instruction = self.get_instruction(*program.key, AggregatorLockParams {})?;
+        invoke_instruction(&instruction, &self.to_account_infos(), None)
+    }
+
+    pub fn invoke_signed(
+        &self,
+        program: AccountInfo<'info>,
+        signer_seeds: &[&[&[u8]]],
+    ) -> anchor_lang::Result<()> {
+        let instruction = self.get_instruction(*program.key, AggregatorLockParams {})?;
+        invoke_instruction(&instruction, &self.to_account_infos(), Some(signer_seeds))
+    }
+
+    fn to_account_infos(&self) -> Vec<AccountInfo<'info>> {
+        vec![self.aggregator.clone(), self.authority.clone()]
+    }
+
+    fn to_account_metas(&self) -> Vec<AccountMeta> {
+        authority_account_metas(&self.aggregator, &self.authority, None)
+    }
+}
+
+// Instruction data is the discriminator followed by the borsh encoded params.
+// Every configuration instruction takes the aggregator and its authority, plus at most one other
+// account such as the job being added.
+fn authority_account_metas(
+    aggregator: &AccountInfo,
+    authority: &AccountInfo,
+    extra: Option<&AccountInfo>,
+) -> Vec<AccountMeta> {
+    let mut accounts = vec![
+        AccountMeta {
+            pubkey: *aggregator.key,
+            is_signer: aggregator.is_signer,
+            is_writable: aggregator.is_writable,
+        },
+        AccountMeta {
+            pubkey: *authority.key,
+            is_signer: true, // overwrite, authority has to sign
+            is_writable: authority.is_writable,
+        },
+    ];
+    if let Some(extra) = extra {
+        accounts.push(AccountMeta {
+            pubkey: *extra.key,
+            is_signer: extra.is_signer,
+            is_writable: extra.is_writable,
+        });
+    }
+    accounts
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::decimal::SwitchboardDecimal;
+
+    #[test]
+    fn test_aggregator_config_instructions() {
+        let keys = [
+            Pubkey::new_unique(),
+            Pubkey::new_unique(),
+            Pubkey::new_unique(),
+        ];
+        let mut lamports = [0u64; 3];
+        let mut data: [Vec<u8>; 3] = Default::default();
+        let [l0, l1, l2] = &mut lamports;
+        let [d0, d1, d2] = &mut data;
+        let aggregator = AccountInfo::new(&keys[0], false, true, l0, d0, &keys[0], false, 0);
+        let authority = AccountInfo::new(&keys[1], false, false, l1, d1, &keys[1], false, 0);
+        let job = AccountInfo::new(&keys[2], false, true, l2, d2, &keys[2], false, 0);
+
+        let ix = AggregatorSetVarianceThreshold {
+            aggregator: aggregator.clone(),
+            authority: authority.clone(),
+        }
+        .get_instruction(
+            crate::SWITCHBOARD_V2_DEVNET,
+            AggregatorSetVarianceThresholdParams {
+                variance_threshold: SwitchboardDecimal::new(15, 1).into(),
+            },
+        )
+        .unwrap();
+        let mut expected = vec![212, 41, 238, 231, 119, 125, 150, 6];
+        expected.extend_from_slice(&15i128.to_le_bytes());
+        expected.extend_from_slice(&1u32.to_le_bytes());
+        assert_eq!(ix.data, expected);
+        assert_eq!(
+            ix.accounts,
+            vec![
+                AccountMeta::new(keys[0], false),
+                AccountMeta::new_readonly(keys[1], true),
+            ]
+        );
+
+        let add_job = AggregatorAddJob {
+            aggregator: aggregator.clone(),
+            authority: authority.clone(),
+            job: job.clone(),
+        };
+        let ix = add_job
+            .get_instruction(
+                crate::SWITCHBOARD_V2_DEVNET,
+                AggregatorAddJobParams { weight: Some(3) },
+            )
+            .unwrap();
+        assert_eq!(ix.data, vec![132, 30, 35, 51, 115, 142, 186, 10, 1, 3]);
+        assert_eq!(ix.accounts[2], AccountMeta::new(keys[2], false));
+        let ix = add_job
+            .get_instruction(
+                crate::SWITCHBOARD_V2_DEVNET,
+                AggregatorAddJobParams { weight: None },
+            )
+            .unwrap();
+        assert_eq!(ix.data[8..], [0]);
+
+        let ix = AggregatorLock {
+            aggregator,
+            authority,
+        }
+        .get_instruction(crate::SWITCHBOARD_V2_DEVNET, AggregatorLockParams {})
+        .unwrap();
+        assert_eq!(ix.data, vec![228, 238, 67, 53, 69, 176, 185, 227]);
+    }
+}
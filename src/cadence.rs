@@ -0,0 +1,188 @@
+use super::aggregator::AggregatorAccountData;
+use super::error::SwitchboardError;
+use super::history_buffer::AggregatorHistoryRow;
+use anchor_lang::prelude::*;
+
+/// A stretch of time without any history buffer updates.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub struct UpdateGap {
+    // Timestamp of the last update before the gap.
+    pub start_timestamp: i64,
+    // Timestamp of the first update after the gap, or the analysis time for a gap that is still open.
+    pub end_timestamp: i64,
+}
+
+impl UpdateGap {
+    pub fn duration(&self) -> i64 {
+        self.end_timestamp - self.start_timestamp
+    }
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct UpdateCadenceReport {
+    pub num_updates: usize,
+    // Gaps longer than the requested threshold, oldest first.
+    pub gaps: Vec<UpdateGap>,
+    // Nearest-rank median of the seconds between consecutive updates.
+    pub median_interval: i64,
+    // Nearest-rank 95th percentile of the seconds between consecutive updates.
+    pub p95_interval: i64,
+    pub max_interval: i64,
+    // Seconds since the latest update at the time of analysis.
+    pub staleness: i64,
+    // Aggregator configs the intervals are compared against.
+    pub min_update_delay_seconds: u32,
+    pub force_report_period: i64,
+}
+
+impl UpdateCadenceReport {
+    /// the feed typically updates slower than its configured update delay
+    pub fn median_exceeds_min_update_delay(&self) -> bool {
+        self.median_interval > self.min_update_delay_seconds as i64
+    }
+
+    /// the slowest 5% of updates came later than oracles are forced to report
+    pub fn p95_exceeds_force_report_period(&self) -> bool {
+        self.force_report_period > 0 && self.p95_interval > self.force_report_period
+    }
+
+    /// the feed has not updated within its force report period
+    pub fn is_stale(&self) -> bool {
+        self.force_report_period > 0 && self.staleness > self.force_report_period
+    }
+
+    pub fn is_healthy(&self) -> bool {
+        !self.p95_exceeds_force_report_period() && !self.is_stale()
+    }
+}
+
+/// Analyze the spacing between history buffer updates. Rows are expected in chronological order,
+/// see `AggregatorHistoryBuffer::iter`. Any gap longer than `gap_threshold` seconds is reported,
+/// including the open gap between the latest update and `now`.
+pub fn analyze_update_cadence<'r, I>(
+    rows: I,
+    aggregator: &AggregatorAccountData,
+    gap_threshold: i64,
+    now: i64,
+) -> anchor_lang::Result<UpdateCadenceReport>
+where
+    I: IntoIterator<Item = &'r AggregatorHistoryRow>,
+{
+    let mut num_updates = 0;
+    let mut intervals: Vec<i64> = Vec::new();
+    let mut gaps: Vec<UpdateGap> = Vec::new();
+    let mut last_timestamp: Option<i64> = None;
+
+    for row in rows {
+        let timestamp: i64 = row.timestamp;
+        if let Some(last_timestamp) = last_timestamp {
+            let gap = UpdateGap {
+                start_timestamp: last_timestamp,
+                end_timestamp: timestamp,
+            };
+            if gap.duration() > gap_threshold {
+                gaps.push(gap);
+            }
+            intervals.push(gap.duration());
+        }
+        last_timestamp = Some(timestamp);
+        num_updates += 1;
+    }
+    let last_timestamp = match last_timestamp {
+        Some(last_timestamp) if !intervals.is_empty() => last_timestamp,
+        _ => return Err(error!(SwitchboardError::InsufficientHistoryError)),
+    };
+
+    let open_gap = UpdateGap {
+        start_timestamp: last_timestamp,
+        end_timestamp: now,
+    };
+    if open_gap.duration() > gap_threshold {
+        gaps.push(open_gap);
+    }
+
+    intervals.sort_unstable();
+    Ok(UpdateCadenceReport {
+        num_updates,
+        gaps,
+        median_interval: percentile(&intervals, 50),
+        p95_interval: percentile(&intervals, 95),
+        max_interval: intervals[intervals.len() - 1],
+        staleness: open_gap.duration(),
+        min_update_delay_seconds: aggregator.min_update_delay_seconds,
+        force_report_period: aggregator.force_report_period,
+    })
+}
+
+// nearest-rank percentile of a sorted, non-empty slice
+fn percentile(sorted: &[i64], pct: usize) -> i64 {
+    let rank = (pct * sorted.len() + 99) / 100;
+    sorted[rank.max(1) - 1]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::decimal::SwitchboardDecimal;
+
+    fn rows(timestamps: &[i64]) -> Vec<AggregatorHistoryRow> {
+        timestamps
+            .iter()
+            .map(|timestamp| AggregatorHistoryRow {
+                timestamp: *timestamp,
+                value: SwitchboardDecimal::from_f64(1.0),
+            })
+            .collect()
+    }
+
+    fn create_aggregator() -> AggregatorAccountData {
+        let mut aggregator = AggregatorAccountData::default();
+        aggregator.min_update_delay_seconds = 10;
+        aggregator.force_report_period = 60;
+        aggregator
+    }
+
+    #[test]
+    fn test_healthy_cadence() {
+        let rows = rows(&[100, 110, 121, 130, 142, 150]);
+        let report = analyze_update_cadence(rows.iter(), &create_aggregator(), 30, 155).unwrap();
+        assert_eq!(report.num_updates, 6);
+        assert!(report.gaps.is_empty());
+        assert_eq!(report.median_interval, 10);
+        assert_eq!(report.p95_interval, 12);
+        assert_eq!(report.max_interval, 12);
+        assert_eq!(report.staleness, 5);
+        assert!(!report.median_exceeds_min_update_delay());
+        assert!(report.is_healthy());
+    }
+
+    #[test]
+    fn test_reports_gaps() {
+        let rows = rows(&[100, 110, 200, 210, 220]);
+        let report = analyze_update_cadence(rows.iter(), &create_aggregator(), 30, 300).unwrap();
+        assert_eq!(
+            report.gaps,
+            vec![
+                UpdateGap {
+                    start_timestamp: 110,
+                    end_timestamp: 200
+                },
+                UpdateGap {
+                    start_timestamp: 220,
+                    end_timestamp: 300
+                },
+            ]
+        );
+        assert_eq!(report.max_interval, 90);
+        assert!(report.p95_exceeds_force_report_period());
+        assert!(report.is_stale());
+        assert!(!report.is_healthy());
+    }
+
+    #[test]
+    fn test_insufficient_history() {
+        let aggregator = create_aggregator();
+        assert!(analyze_update_cadence(rows(&[]).iter(), &aggregator, 30, 100).is_err());
+        assert!(analyze_update_cadence(rows(&[100]).iter(), &aggregator, 30, 100).is_err());
+    }
+}
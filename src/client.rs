@@ -0,0 +1,877 @@
+//! Off-chain transaction builders for tooling that creates Switchboard accounts or runs an oracle.
+//!
+//! ```ignore
+//! let feed = client::create_feed(&SWITCHBOARD_V2_DEVNET, &payer, &authority, &funder, &queue, &config, &rent)?;
+//! let mut signers = vec![&payer_keypair, &authority_keypair];
+//! signers.extend(feed.signers());
+//! ```
+use super::aggregator_config::{AggregatorAddJob, AggregatorAddJobParams};
+use super::crank::{CrankAccountData, CrankRow};
+use super::decimal::SwitchboardDecimal;
+use super::error::SwitchboardError;
+use super::instruction::*;
+use super::lease::{LeaseInit, LeaseInitParams};
+use super::oracle_queue::OracleQueueAccountData;
+use super::permission::{PermissionInit, PermissionInitParams};
+use super::AggregatorAccountData;
+use anchor_lang::prelude::*;
+use solana_program::instruction::Instruction;
+use solana_program::pubkey::Pubkey;
+use solana_program::{system_instruction, system_program, sysvar};
+use solana_sdk::signature::Keypair;
+use solana_sdk::signer::Signer;
+
+pub use super::aggregator::{AggregatorInitParams, AggregatorSaveResultParams, JobInitParams};
+pub use super::crank::{CrankPopParams, CrankPushParams};
+pub use super::lease::ASSOCIATED_TOKEN_PROGRAM_ID;
+pub use super::oracle_queue::OracleHeartbeatParams;
+
+// Job accounts are allocated this many bytes on top of their serialized tasks. Mirrors the base
+// size the Switchboard v2 TypeScript client's `JobAccount.create` allocates.
+const JOB_ACCOUNT_BASE_SIZE: usize = 280;
+
+/// An oracle's response to an aggregator round.
+#[derive(Clone, Debug)]
+pub struct SaveResult {
+    // Slot the round was opened at, identifying the round being answered.
+    pub round_open_slot: u64,
+    pub value: SwitchboardDecimal,
+    pub min_response: SwitchboardDecimal,
+    pub max_response: SwitchboardDecimal,
+    // Hashes of the jobs the oracle ran, in the aggregator's job order.
+    pub job_hashes: Vec<[u8; 32]>,
+    pub error: bool,
+}
+
+/// A job to create and attach to the new feed. `data` is the serialized OracleJob protobuf.
+#[derive(Clone, Debug)]
+pub struct JobConfig {
+    pub name: String,
+    pub data: Vec<u8>,
+    pub weight: Option<u8>,
+}
+
+/// Crank the new feed is pushed onto once its lease is funded.
+#[derive(Clone, Copy, Debug)]
+pub struct FeedCrank {
+    pub crank: Pubkey,
+    pub data_buffer: Pubkey,
+}
+
+#[derive(Clone, Debug)]
+pub struct FeedConfig {
+    pub name: String,
+    pub metadata: String,
+    pub queue: Pubkey,
+    pub jobs: Vec<JobConfig>,
+    pub batch_size: u32,
+    pub min_oracle_results: u32,
+    pub min_job_results: u32,
+    pub variance_threshold: SwitchboardDecimal,
+    pub min_update_delay_seconds: u32,
+    pub force_report_period: i64,
+    // Tokens moved from the funder into the lease escrow.
+    pub lease_amount: u64,
+    pub crank: Option<FeedCrank>,
+}
+
+/// Ordered instructions creating a feed, along with the new accounts that must sign them.
+pub struct FeedInstructions {
+    pub aggregator: Keypair,
+    pub jobs: Vec<Keypair>,
+    pub permission: Pubkey,
+    pub lease: Pubkey,
+    pub escrow: Pubkey,
+    pub instructions: Vec<Instruction>,
+}
+
+impl FeedInstructions {
+    /// Keypairs generated by the builder. The payer and authority must sign as well.
+    pub fn signers(&self) -> Vec<&Keypair> {
+        let mut signers = vec![&self.aggregator];
+        signers.extend(self.jobs.iter());
+        signers
+    }
+}
+
+/// Build every instruction needed to stand up a feed on `config.queue`: the aggregator, its jobs,
+/// the queue permission, a funded lease and optionally a crank push.
+///
+/// `funder` must be the authority's associated token account for the queue mint. It pays the lease
+/// and is each job's author wallet, so it is also the wallet lease_init pays job rewards to.
+pub fn create_feed(
+    program_id: &Pubkey,
+    payer: &Pubkey,
+    authority: &Pubkey,
+    funder: &Pubkey,
+    queue: &OracleQueueAccountData,
+    config: &FeedConfig,
+    rent: &Rent,
+) -> anchor_lang::Result<FeedInstructions> {
+    let queue_key = config.queue;
+    let queue_authority = queue.authority;
+    let mint = queue.mint;
+    // lease_init pays job rewards to the funder under the bump of the authority's wallet
+    let (authority_wallet, wallet_bump) = associated_token_address(authority, &mint);
+    if *funder != authority_wallet {
+        return Err(error!(SwitchboardError::FunderMismatch));
+    }
+    let (program_state, state_bump) = program_state_address(program_id);
+
+    let aggregator = Keypair::new();
+    let aggregator_key = aggregator.pubkey();
+    let mut instructions = Vec::new();
+
+    let aggregator_size = 8 + std::mem::size_of::<AggregatorAccountData>();
+    instructions.push(system_instruction::create_account(
+        payer,
+        &aggregator_key,
+        rent.minimum_balance(aggregator_size),
+        aggregator_size as u64,
+        program_id,
+    ));
+    instructions.push(build_instruction(
+        *program_id,
+        AGGREGATOR_INIT_DISCRIMINATOR,
+        AggregatorInitParams {
+            name: to_fixed_bytes(&config.name),
+            metadata: to_fixed_bytes(&config.metadata),
+            batch_size: config.batch_size,
+            min_oracle_results: config.min_oracle_results,
+            min_job_results: config.min_job_results,
+            min_update_delay_seconds: config.min_update_delay_seconds,
+            start_after: 0,
+            variance_threshold: config.variance_threshold.into(),
+            force_report_period: config.force_report_period,
+            expiration: 0,
+            state_bump,
+        },
+        vec![
+            AccountMeta::new(aggregator_key, false),
+            AccountMeta::new_readonly(*authority, false),
+            AccountMeta::new_readonly(queue_key, false),
+            AccountMeta::new_readonly(*funder, false),
+            AccountMeta::new_readonly(program_state, false),
+        ],
+    )?);
+
+    // lease_init takes the author wallet of every job, with the bump it is checked against
+    let mut jobs = Vec::with_capacity(config.jobs.len());
+    let mut job_wallets = Vec::with_capacity(config.jobs.len());
+    for job_config in config.jobs.iter() {
+        let job = Keypair::new();
+        let job_size = JOB_ACCOUNT_BASE_SIZE + job_config.data.len();
+        instructions.push(system_instruction::create_account(
+            payer,
+            &job.pubkey(),
+            rent.minimum_balance(job_size),
+            job_size as u64,
+            program_id,
+        ));
+        instructions.push(build_instruction(
+            *program_id,
+            JOB_INIT_DISCRIMINATOR,
+            JobInitParams {
+                name: to_fixed_bytes(&job_config.name),
+                expiration: 0,
+                state_bump,
+                data: job_config.data.clone(),
+            },
+            vec![
+                AccountMeta::new(job.pubkey(), false),
+                AccountMeta::new_readonly(*funder, false),
+                AccountMeta::new_readonly(program_state, false),
+            ],
+        )?);
+        instructions.push(build_instruction(
+            *program_id,
+            AggregatorAddJob::discriminator(),
+            AggregatorAddJobParams {
+                weight: job_config.weight,
+            },
+            vec![
+                AccountMeta::new(aggregator_key, false),
+                AccountMeta::new_readonly(*authority, true),
+                AccountMeta::new(job.pubkey(), false),
+            ],
+        )?);
+        job_wallets.push((*funder, wallet_bump));
+        jobs.push(job);
+    }
+
+    let (permission, permission_bump) =
+        permission_address(program_id, &queue_authority, &queue_key, &aggregator_key);
+    instructions.push(build_instruction(
+        *program_id,
+        PermissionInit::discriminator(),
+        PermissionInitParams { permission_bump },
+        vec![
+            AccountMeta::new(permission, false),
+            AccountMeta::new_readonly(queue_authority, false),
+            AccountMeta::new_readonly(queue_key, false),
+            AccountMeta::new_readonly(aggregator_key, false),
+            AccountMeta::new(*payer, true),
+            AccountMeta::new_readonly(system_program::ID, false),
+        ],
+    )?);
+
+    let (lease, lease_bump) = lease_address(program_id, &queue_key, &aggregator_key);
+    let (escrow, _) = associated_token_address(&lease, &mint);
+    instructions.push(create_associated_token_account(payer, &lease, &mint));
+
+    let mut lease_accounts = vec![
+        AccountMeta::new(lease, false),
+        AccountMeta::new(queue_key, false),
+        AccountMeta::new_readonly(aggregator_key, false),
+        AccountMeta::new(*funder, false),
+        AccountMeta::new(*payer, true),
+        AccountMeta::new_readonly(system_program::ID, false),
+        AccountMeta::new_readonly(anchor_spl::token::ID, false),
+        AccountMeta::new_readonly(*authority, true),
+        AccountMeta::new(escrow, false),
+        AccountMeta::new_readonly(program_state, false),
+        AccountMeta::new_readonly(mint, false),
+    ];
+    lease_accounts.extend(
+        job_wallets
+            .iter()
+            .map(|(wallet, _)| AccountMeta::new(*wallet, false)),
+    );
+    instructions.push(build_instruction(
+        *program_id,
+        LeaseInit::discriminator(),
+        LeaseInitParams {
+            load_amount: config.lease_amount,
+            withdraw_authority: *authority,
+            lease_bump,
+            state_bump,
+            wallet_bumps: job_wallets.iter().map(|(_, bump)| *bump).collect(),
+        },
+        lease_accounts,
+    )?);
+
+    if let Some(crank) = config.crank {
+        instructions.push(crank_push_instruction(
+            program_id,
+            &crank,
+            &queue_key,
+            queue,
+            &aggregator_key,
+        )?);
+    }
+
+    Ok(FeedInstructions {
+        aggregator,
+        jobs,
+        permission,
+        lease,
+        escrow,
+        instructions,
+    })
+}
+
+/// Heartbeat keeping `oracle` on the queue. `gc_oracle` is the oracle at the queue's garbage
+/// collection index, which the program removes if its heartbeat has expired.
+pub fn oracle_heartbeat(
+    program_id: &Pubkey,
+    oracle: &Pubkey,
+    oracle_authority: &Pubkey,
+    token_account: &Pubkey,
+    gc_oracle: &Pubkey,
+    queue_key: &Pubkey,
+    queue: &OracleQueueAccountData,
+) -> anchor_lang::Result<Instruction> {
+    let (permission, permission_bump) =
+        permission_address(program_id, &queue.authority, queue_key, oracle);
+    build_instruction(
+        *program_id,
+        ORACLE_HEARTBEAT_DISCRIMINATOR,
+        OracleHeartbeatParams { permission_bump },
+        vec![
+            AccountMeta::new(*oracle, false),
+            AccountMeta::new_readonly(*oracle_authority, true),
+            AccountMeta::new_readonly(*token_account, false),
+            AccountMeta::new(*gc_oracle, false),
+            AccountMeta::new(*queue_key, false),
+            AccountMeta::new_readonly(permission, false),
+            AccountMeta::new(queue.data_buffer, false),
+        ],
+    )
+}
+
+/// Save `oracle`'s response to the aggregator's current round.
+///
+/// Fails if `result` answers a different round or `oracle` was not assigned to the round.
+#[allow(clippy::too_many_arguments)]
+pub fn aggregator_save_result(
+    program_id: &Pubkey,
+    aggregator_key: &Pubkey,
+    aggregator: &AggregatorAccountData,
+    oracle: &Pubkey,
+    oracle_authority: &Pubkey,
+    queue: &OracleQueueAccountData,
+    history_buffer: &Pubkey,
+    result: &SaveResult,
+) -> anchor_lang::Result<Instruction> {
+    let round_open_slot = aggregator.current_round.round_open_slot;
+    if result.round_open_slot != round_open_slot {
+        return Err(error!(SwitchboardError::InvalidAggregatorRound));
+    }
+    let oracles = aggregator.current_round.oracle_pubkeys_data;
+    let batch_size = (aggregator.oracle_request_batch_size as usize).min(oracles.len());
+    let oracle_idx = oracles[..batch_size]
+        .iter()
+        .position(|key| key == oracle)
+        .ok_or(error!(SwitchboardError::OracleMismatchError))?;
+
+    let queue_key = aggregator.queue_pubkey;
+    let (program_state, state_bump) = program_state_address(program_id);
+    let (feed_permission, feed_permission_bump) =
+        permission_address(program_id, &queue.authority, &queue_key, aggregator_key);
+    let (oracle_permission, oracle_permission_bump) =
+        permission_address(program_id, &queue.authority, &queue_key, oracle);
+    let (lease, lease_bump) = lease_address(program_id, &queue_key, aggregator_key);
+    let (escrow, _) = associated_token_address(&lease, &queue.mint);
+    let job_hashes: Vec<&[u8]> = result.job_hashes.iter().map(|hash| &hash[..]).collect();
+
+    build_instruction(
+        *program_id,
+        AGGREGATOR_SAVE_RESULT_DISCRIMINATOR,
+        AggregatorSaveResultParams {
+            oracle_idx: oracle_idx as u32,
+            error: result.error,
+            value: result.value.into(),
+            jobs_checksum: solana_program::hash::hashv(&job_hashes).to_bytes(),
+            min_response: result.min_response.into(),
+            max_response: result.max_response.into(),
+            feed_permission_bump,
+            oracle_permission_bump,
+            lease_bump,
+            state_bump,
+        },
+        vec![
+            AccountMeta::new(*aggregator_key, false),
+            AccountMeta::new(*oracle, false),
+            AccountMeta::new_readonly(*oracle_authority, true),
+            AccountMeta::new_readonly(queue_key, false),
+            AccountMeta::new_readonly(queue.authority, false),
+            AccountMeta::new(feed_permission, false),
+            AccountMeta::new_readonly(oracle_permission, false),
+            AccountMeta::new(lease, false),
+            AccountMeta::new(escrow, false),
+            AccountMeta::new_readonly(anchor_spl::token::ID, false),
+            AccountMeta::new_readonly(program_state, false),
+            AccountMeta::new(*history_buffer, false),
+            AccountMeta::new_readonly(queue.mint, false),
+        ],
+    )
+}
+
+/// Push `aggregator_key` onto the crank. The aggregator needs a funded lease and, on permissioned
+/// queues, a queue usage permission.
+pub fn crank_push(
+    program_id: &Pubkey,
+    crank_key: &Pubkey,
+    crank: &CrankAccountData,
+    aggregator_key: &Pubkey,
+    queue: &OracleQueueAccountData,
+) -> anchor_lang::Result<Instruction> {
+    let feed_crank = FeedCrank {
+        crank: *crank_key,
+        data_buffer: crank.data_buffer,
+    };
+    crank_push_instruction(
+        program_id,
+        &feed_crank,
+        &crank.queue_pubkey,
+        queue,
+        aggregator_key,
+    )
+}
+
+/// Pop up to `max_rows` rows that are ready at `now` off the crank, paying the crank reward to
+/// `payout_wallet`. `rows` are the crank's rows, see `CrankAccountData::rows`.
+///
+/// The lease and permission bumps hold one entry per popped row, in pop order. The remaining
+/// accounts are every popped row's aggregator, lease, escrow and permission, sorted by pubkey with
+/// duplicates removed, which the program looks each row's accounts up in.
+#[allow(clippy::too_many_arguments)]
+pub fn crank_pop(
+    program_id: &Pubkey,
+    crank_key: &Pubkey,
+    crank: &CrankAccountData,
+    rows: &[CrankRow],
+    queue: &OracleQueueAccountData,
+    payout_wallet: &Pubkey,
+    now: i64,
+    max_rows: usize,
+) -> anchor_lang::Result<Instruction> {
+    let ready = CrankAccountData::ready_rows(rows, now, max_rows);
+    if ready.is_empty() {
+        return Err(error!(SwitchboardError::CrankEmptyError));
+    }
+
+    let queue_key = crank.queue_pubkey;
+    let (program_state, state_bump) = program_state_address(program_id);
+    let mut lease_bumps = Vec::with_capacity(ready.len());
+    let mut permission_bumps = Vec::with_capacity(ready.len());
+    let mut remaining_accounts = Vec::with_capacity(ready.len() * 4);
+    for row in ready.iter() {
+        let aggregator_key = row.pubkey;
+        let (lease, lease_bump) = lease_address(program_id, &queue_key, &aggregator_key);
+        let (escrow, _) = associated_token_address(&lease, &queue.mint);
+        let (permission, permission_bump) =
+            permission_address(program_id, &queue.authority, &queue_key, &aggregator_key);
+        remaining_accounts.extend_from_slice(&[aggregator_key, lease, escrow, permission]);
+        lease_bumps.push(lease_bump);
+        permission_bumps.push(permission_bump);
+    }
+    remaining_accounts.sort();
+    remaining_accounts.dedup();
+
+    let mut accounts = vec![
+        AccountMeta::new(*crank_key, false),
+        AccountMeta::new(queue_key, false),
+        AccountMeta::new_readonly(queue.authority, false),
+        AccountMeta::new_readonly(program_state, false),
+        AccountMeta::new(*payout_wallet, false),
+        AccountMeta::new_readonly(anchor_spl::token::ID, false),
+        AccountMeta::new(crank.data_buffer, false),
+        AccountMeta::new_readonly(queue.data_buffer, false),
+        AccountMeta::new_readonly(queue.mint, false),
+    ];
+    accounts.extend(
+        remaining_accounts
+            .into_iter()
+            .map(|pubkey| AccountMeta::new(pubkey, false)),
+    );
+
+    build_instruction(
+        *program_id,
+        CRANK_POP_DISCRIMINATOR,
+        CrankPopParams {
+            state_bump,
+            lease_bumps,
+            permission_bumps,
+            nonce: None,
+            fail_open_on_account_mismatch: None,
+        },
+        accounts,
+    )
+}
+
+fn crank_push_instruction(
+    program_id: &Pubkey,
+    crank: &FeedCrank,
+    queue_key: &Pubkey,
+    queue: &OracleQueueAccountData,
+    aggregator_key: &Pubkey,
+) -> anchor_lang::Result<Instruction> {
+    let (program_state, state_bump) = program_state_address(program_id);
+    let (permission, permission_bump) =
+        permission_address(program_id, &queue.authority, queue_key, aggregator_key);
+    let (lease, _) = lease_address(program_id, queue_key, aggregator_key);
+    let (escrow, _) = associated_token_address(&lease, &queue.mint);
+    build_instruction(
+        *program_id,
+        CRANK_PUSH_DISCRIMINATOR,
+        CrankPushParams {
+            state_bump,
+            permission_bump,
+        },
+        vec![
+            AccountMeta::new(crank.crank, false),
+            AccountMeta::new(*aggregator_key, false),
+            AccountMeta::new(*queue_key, false),
+            AccountMeta::new_readonly(queue.authority, false),
+            AccountMeta::new_readonly(permission, false),
+            AccountMeta::new(lease, false),
+            AccountMeta::new(escrow, false),
+            AccountMeta::new_readonly(program_state, false),
+            AccountMeta::new(crank.data_buffer, false),
+        ],
+    )
+}
+
+/// Program state PDA, with its bump.
+pub fn program_state_address(program_id: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[b"STATE"], program_id)
+}
+
+/// Permission PDA letting `grantee` use `granter`, with its bump.
+pub fn permission_address(
+    program_id: &Pubkey,
+    authority: &Pubkey,
+    granter: &Pubkey,
+    grantee: &Pubkey,
+) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &[
+            b"PermissionAccountData",
+            authority.as_ref(),
+            granter.as_ref(),
+            grantee.as_ref(),
+        ],
+        program_id,
+    )
+}
+
+/// Lease PDA funding `aggregator` on `queue`, with its bump.
+pub fn lease_address(program_id: &Pubkey, queue: &Pubkey, aggregator: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &[b"LeaseAccountData", queue.as_ref(), aggregator.as_ref()],
+        program_id,
+    )
+}
+
+/// Associated token account of `wallet` for `mint`, with its bump.
+pub fn associated_token_address(wallet: &Pubkey, mint: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &[
+            wallet.as_ref(),
+            anchor_spl::token::ID.as_ref(),
+            mint.as_ref(),
+        ],
+        &ASSOCIATED_TOKEN_PROGRAM_ID,
+    )
+}
+
+fn create_associated_token_account(payer: &Pubkey, wallet: &Pubkey, mint: &Pubkey) -> Instruction {
+    let (address, _) = associated_token_address(wallet, mint);
+    Instruction::new_with_bytes(
+        ASSOCIATED_TOKEN_PROGRAM_ID,
+        &[],
+        vec![
+            AccountMeta::new(*payer, true),
+            AccountMeta::new(address, false),
+            AccountMeta::new_readonly(*wallet, false),
+            AccountMeta::new_readonly(*mint, false),
+            AccountMeta::new_readonly(system_program::ID, false),
+            AccountMeta::new_readonly(anchor_spl::token::ID, false),
+            AccountMeta::new_readonly(sysvar::rent::ID, false),
+        ],
+    )
+}
+
+fn to_fixed_bytes<const N: usize>(value: &str) -> [u8; N] {
+    let mut bytes = [0u8; N];
+    let len = value.len().min(N);
+    bytes[..len].copy_from_slice(&value.as_bytes()[..len]);
+    bytes
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::decimal::BorshDecimal;
+    use bytemuck::Zeroable;
+
+    #[test]
+    fn test_create_feed() {
+        let program_id = crate::SWITCHBOARD_V2_DEVNET;
+        let payer = Pubkey::new_unique();
+        let authority = Pubkey::new_unique();
+        let mut queue = OracleQueueAccountData::zeroed();
+        queue.authority = Pubkey::new_unique();
+        queue.mint = Pubkey::new_unique();
+        let (funder, _) = associated_token_address(&authority, &queue.mint);
+        let crank = FeedCrank {
+            crank: Pubkey::new_unique(),
+            data_buffer: Pubkey::new_unique(),
+        };
+        let config = FeedConfig {
+            name: "SOL_USD".to_string(),
+            metadata: String::new(),
+            queue: Pubkey::new_unique(),
+            jobs: vec![
+                JobConfig {
+                    name: "ftx".to_string(),
+                    data: vec![1, 2, 3],
+                    weight: None,
+                },
+                JobConfig {
+                    name: "binance".to_string(),
+                    data: vec![4, 5],
+                    weight: Some(2),
+                },
+            ],
+            batch_size: 4,
+            min_oracle_results: 2,
+            min_job_results: 1,
+            variance_threshold: SwitchboardDecimal::new(5, 1),
+            min_update_delay_seconds: 30,
+            force_report_period: 0,
+            lease_amount: 1_000_000,
+            crank: Some(crank),
+        };
+
+        let feed = create_feed(
+            &program_id,
+            &payer,
+            &authority,
+            &funder,
+            &queue,
+            &config,
+            &Rent::default(),
+        )
+        .unwrap();
+        let ixs = &feed.instructions;
+        assert_eq!(ixs.len(), 2 + 3 * 2 + 4);
+        assert_eq!(feed.signers().len(), 3);
+
+        assert_eq!(ixs[0].program_id, system_program::ID);
+        assert_eq!(ixs[0].accounts[1].pubkey, feed.aggregator.pubkey());
+        assert_eq!(ixs[1].data[..8], [200, 41, 88, 11, 36, 21, 181, 110]);
+        let params = AggregatorInitParams::deserialize(&mut &ixs[1].data[8..]).unwrap();
+        assert_eq!(&params.name[..7], b"SOL_USD");
+        assert_eq!(params.batch_size, 4);
+
+        assert_eq!(ixs[3].data[..8], [101, 86, 105, 192, 34, 201, 147, 159]);
+        assert_eq!(ixs[4].data[..8], AggregatorAddJob::discriminator());
+        assert_eq!(ixs[7].accounts[2].pubkey, feed.jobs[1].pubkey());
+
+        assert_eq!(ixs[8].data[..8], [177, 116, 201, 233, 16, 2, 11, 179]);
+        assert_eq!(ixs[8].accounts[0].pubkey, feed.permission);
+        assert_eq!(ixs[9].program_id, ASSOCIATED_TOKEN_PROGRAM_ID);
+        assert_eq!(ixs[9].accounts[1].pubkey, feed.escrow);
+
+        let (lease, _) = Pubkey::find_program_address(
+            &[
+                b"LeaseAccountData",
+                config.queue.as_ref(),
+                feed.aggregator.pubkey().as_ref(),
+            ],
+            &program_id,
+        );
+        assert_eq!(feed.lease, lease);
+        assert_eq!(ixs[10].data[..8], LeaseInit::discriminator());
+        assert_eq!(ixs[10].accounts.len(), 11 + 2);
+        let params = LeaseInitParams::deserialize(&mut &ixs[10].data[8..]).unwrap();
+        assert_eq!(params.load_amount, 1_000_000);
+        assert_eq!(params.wallet_bumps.len(), 2);
+        // job rewards go to the author wallet each job was created with
+        let author_wallets: Vec<Pubkey> = [3, 6]
+            .iter()
+            .map(|idx| ixs[*idx].accounts[1].pubkey)
+            .collect();
+        let lease_wallets: Vec<Pubkey> = ixs[10].accounts[11..]
+            .iter()
+            .map(|meta| meta.pubkey)
+            .collect();
+        assert_eq!(author_wallets, vec![funder, funder]);
+        assert_eq!(lease_wallets, author_wallets);
+
+        assert_eq!(ixs[11].data[..8], [155, 175, 160, 18, 7, 147, 249, 16]);
+        assert_eq!(ixs[11].accounts[0].pubkey, crank.crank);
+
+        // the wallet bumps only hold for the authority's own wallet
+        let other_funder = Pubkey::new_unique();
+        assert_eq!(
+            create_feed(
+                &program_id,
+                &payer,
+                &authority,
+                &other_funder,
+                &queue,
+                &config,
+                &Rent::default(),
+            )
+            .err()
+            .unwrap(),
+            error!(SwitchboardError::FunderMismatch)
+        );
+    }
+
+    #[test]
+    fn test_oracle_instructions() {
+        let program_id = crate::SWITCHBOARD_V2_DEVNET;
+        let oracle = Pubkey::new_unique();
+        let oracle_authority = Pubkey::new_unique();
+        let aggregator_key = Pubkey::new_unique();
+        let history_buffer = Pubkey::new_unique();
+        let mut queue = OracleQueueAccountData::zeroed();
+        queue.authority = Pubkey::new_unique();
+        queue.mint = Pubkey::new_unique();
+        queue.data_buffer = Pubkey::new_unique();
+        let queue_key = Pubkey::new_unique();
+
+        let heartbeat = oracle_heartbeat(
+            &program_id,
+            &oracle,
+            &oracle_authority,
+            &Pubkey::new_unique(),
+            &oracle,
+            &queue_key,
+            &queue,
+        )
+        .unwrap();
+        assert_eq!(heartbeat.data[..8], [10, 175, 217, 130, 111, 35, 117, 54]);
+        assert!(heartbeat.accounts[1].is_signer);
+        assert_eq!(heartbeat.accounts[6].pubkey, queue.data_buffer);
+
+        let mut aggregator = AggregatorAccountData::zeroed();
+        aggregator.queue_pubkey = queue_key;
+        aggregator.oracle_request_batch_size = 2;
+        aggregator.current_round.round_open_slot = 100;
+        aggregator.current_round.oracle_pubkeys_data[1] = oracle;
+        let mut result = SaveResult {
+            round_open_slot: 100,
+            value: SwitchboardDecimal::new(12345, 2),
+            min_response: SwitchboardDecimal::new(12300, 2),
+            max_response: SwitchboardDecimal::new(12400, 2),
+            job_hashes: vec![[1; 32], [2; 32]],
+            error: false,
+        };
+        let save_result = aggregator_save_result(
+            &program_id,
+            &aggregator_key,
+            &aggregator,
+            &oracle,
+            &oracle_authority,
+            &queue,
+            &history_buffer,
+            &result,
+        )
+        .unwrap();
+        assert_eq!(save_result.data[..8], [21, 67, 5, 0, 74, 168, 51, 192]);
+        let params = AggregatorSaveResultParams::deserialize(&mut &save_result.data[8..]).unwrap();
+        assert_eq!(params.oracle_idx, 1);
+        assert_eq!(params.value, BorshDecimal::from(result.value));
+        assert_eq!(
+            params.jobs_checksum,
+            solana_program::hash::hashv(&[&[1; 32], &[2; 32]]).to_bytes()
+        );
+        let (lease, lease_bump) = lease_address(&program_id, &queue_key, &aggregator_key);
+        assert_eq!(params.lease_bump, lease_bump);
+        assert_eq!(save_result.accounts[7].pubkey, lease);
+        assert_eq!(save_result.accounts[11].pubkey, history_buffer);
+
+        let other_oracle = Pubkey::new_unique();
+        assert!(aggregator_save_result(
+            &program_id,
+            &aggregator_key,
+            &aggregator,
+            &other_oracle,
+            &oracle_authority,
+            &queue,
+            &history_buffer,
+            &result,
+        )
+        .is_err());
+        result.round_open_slot = 99;
+        assert!(aggregator_save_result(
+            &program_id,
+            &aggregator_key,
+            &aggregator,
+            &oracle,
+            &oracle_authority,
+            &queue,
+            &history_buffer,
+            &result,
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn test_crank_instructions() {
+        let program_id = crate::SWITCHBOARD_V2_DEVNET;
+        let crank_key = Pubkey::new_unique();
+        let payout_wallet = Pubkey::new_unique();
+        let mut queue = OracleQueueAccountData::zeroed();
+        queue.authority = Pubkey::new_unique();
+        queue.mint = Pubkey::new_unique();
+        queue.data_buffer = Pubkey::new_unique();
+        let mut crank = CrankAccountData::zeroed();
+        crank.queue_pubkey = Pubkey::new_unique();
+        crank.data_buffer = Pubkey::new_unique();
+
+        let aggregator_key = Pubkey::new_unique();
+        let push = crank_push(&program_id, &crank_key, &crank, &aggregator_key, &queue).unwrap();
+        assert_eq!(push.data[..8], [155, 175, 160, 18, 7, 147, 249, 16]);
+        assert_eq!(push.accounts[2].pubkey, crank.queue_pubkey);
+        assert_eq!(push.accounts[8].pubkey, crank.data_buffer);
+
+        let rows: Vec<CrankRow> = [50, 10, 500, 20]
+            .iter()
+            .map(|next_timestamp| CrankRow {
+                pubkey: Pubkey::new_unique(),
+                next_timestamp: *next_timestamp,
+            })
+            .collect();
+        let pop = crank_pop(
+            &program_id,
+            &crank_key,
+            &crank,
+            &rows,
+            &queue,
+            &payout_wallet,
+            100,
+            2,
+        )
+        .unwrap();
+        assert_eq!(pop.data[..8], [66, 57, 216, 251, 165, 107, 128, 98]);
+        let params = CrankPopParams::deserialize(&mut &pop.data[8..]).unwrap();
+        let (_, lease_bump) = lease_address(&program_id, &crank.queue_pubkey, &rows[1].pubkey);
+        assert_eq!(params.lease_bumps.len(), 2);
+        assert_eq!(params.lease_bumps[0], lease_bump);
+
+        // two ready rows, each with an aggregator, lease, escrow and permission
+        let remaining: Vec<Pubkey> = pop.accounts[9..].iter().map(|meta| meta.pubkey).collect();
+        assert_eq!(remaining.len(), 8);
+        assert!(remaining.windows(2).all(|pair| pair[0] < pair[1]));
+        assert!(remaining.contains(&rows[1].pubkey));
+        assert!(remaining.contains(&rows[3].pubkey));
+        assert!(!remaining.contains(&rows[0].pubkey));
+
+        assert!(crank_pop(
+            &program_id,
+            &crank_key,
+            &crank,
+            &rows,
+            &queue,
+            &payout_wallet,
+            5,
+            2,
+        )
+        .is_err());
+
+        // rows for the same aggregator share its lease, escrow and permission, which are only
+        // passed once, but each row still gets its bumps
+        let mut shared_rows = rows.clone();
+        shared_rows[0].pubkey = rows[1].pubkey;
+        let pop = crank_pop(
+            &program_id,
+            &crank_key,
+            &crank,
+            &shared_rows,
+            &queue,
+            &payout_wallet,
+            100,
+            3,
+        )
+        .unwrap();
+        let params = CrankPopParams::deserialize(&mut &pop.data[8..]).unwrap();
+        let remaining: Vec<Pubkey> = pop.accounts[9..].iter().map(|meta| meta.pubkey).collect();
+        assert_eq!(remaining.len(), 8);
+        assert!(remaining.windows(2).all(|pair| pair[0] < pair[1]));
+        assert_eq!(params.lease_bumps.len(), 3);
+        assert_eq!(params.permission_bumps.len(), 3);
+        // popped soonest first: rows[1], rows[3], then shared_rows[0]
+        for (idx, row) in [shared_rows[1], shared_rows[3], shared_rows[0]]
+            .iter()
+            .enumerate()
+        {
+            let (lease, lease_bump) = lease_address(&program_id, &crank.queue_pubkey, &row.pubkey);
+            let (permission, permission_bump) = permission_address(
+                &program_id,
+                &queue.authority,
+                &crank.queue_pubkey,
+                &row.pubkey,
+            );
+            assert_eq!(params.lease_bumps[idx], lease_bump);
+            assert_eq!(params.permission_bumps[idx], permission_bump);
+            assert!(remaining.contains(&lease));
+            assert!(remaining.contains(&permission));
+        }
+    }
+}
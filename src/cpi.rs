@@ -0,0 +1,278 @@
+//! Anchor style CPI entry points, laid out like the `cpi` module anchor generates for a program.
+//!
+//! ```ignore
+//! let cpi_ctx = CpiContext::new(switchboard_program, accounts).with_signer(signer_seeds);
+//! switchboard_v2::cpi::request_randomness(cpi_ctx, params)?;
+//! ```
+use super::aggregator::AggregatorOpenRoundParams;
+use super::aggregator_config::*;
+use super::lease::{LeaseExtendParams, LeaseInitParams, LeaseWithdrawParams};
+use super::permission::{PermissionInitParams, PermissionSetParams};
+use super::vrf::{VrfInitParams, VrfRequestRandomnessParams, VrfSetCallbackParams};
+use anchor_lang::prelude::*;
+use solana_program::program::invoke_signed;
+
+pub mod accounts {
+    pub use crate::aggregator::AggregatorOpenRound;
+    pub use crate::aggregator_config::{
+        AggregatorAddJob, AggregatorLock, AggregatorRemoveJob, AggregatorSetAuthority,
+        AggregatorSetBatchSize, AggregatorSetMinJobs, AggregatorSetMinOracles,
+        AggregatorSetUpdateInterval, AggregatorSetVarianceThreshold,
+    };
+    pub use crate::lease::{LeaseExtend, LeaseInit, LeaseWithdraw};
+    pub use crate::permission::{PermissionInit, PermissionSet};
+    pub use crate::vrf::{VrfInit, VrfRequestRandomness, VrfSetCallback};
+}
+
+pub fn request_randomness<'info>(
+    ctx: CpiContext<'_, '_, '_, 'info, accounts::VrfRequestRandomness<'info>>,
+    params: VrfRequestRandomnessParams,
+) -> anchor_lang::Result<()> {
+    let instruction = ctx.accounts.get_instruction(*ctx.program.key, params)?;
+    invoke_signed(&instruction, &ctx.to_account_infos(), ctx.signer_seeds).map_err(Into::into)
+}
+
+pub fn vrf_init<'info>(
+    ctx: CpiContext<'_, '_, '_, 'info, accounts::VrfInit<'info>>,
+    params: VrfInitParams,
+) -> anchor_lang::Result<()> {
+    let instruction = ctx.accounts.get_instruction(*ctx.program.key, params)?;
+    invoke_signed(&instruction, &ctx.to_account_infos(), ctx.signer_seeds).map_err(Into::into)
+}
+
+pub fn vrf_set_callback<'info>(
+    ctx: CpiContext<'_, '_, '_, 'info, accounts::VrfSetCallback<'info>>,
+    params: VrfSetCallbackParams,
+) -> anchor_lang::Result<()> {
+    let instruction = ctx.accounts.get_instruction(*ctx.program.key, params)?;
+    invoke_signed(&instruction, &ctx.to_account_infos(), ctx.signer_seeds).map_err(Into::into)
+}
+
+pub fn aggregator_open_round<'info>(
+    ctx: CpiContext<'_, '_, '_, 'info, accounts::AggregatorOpenRound<'info>>,
+    params: AggregatorOpenRoundParams,
+) -> anchor_lang::Result<()> {
+    let instruction = ctx.accounts.get_instruction(*ctx.program.key, params)?;
+    invoke_signed(&instruction, &ctx.to_account_infos(), ctx.signer_seeds).map_err(Into::into)
+}
+
+pub fn aggregator_set_variance_threshold<'info>(
+    ctx: CpiContext<'_, '_, '_, 'info, accounts::AggregatorSetVarianceThreshold<'info>>,
+    params: AggregatorSetVarianceThresholdParams,
+) -> anchor_lang::Result<()> {
+    let instruction = ctx.accounts.get_instruction(*ctx.program.key, params)?;
+    invoke_signed(&instruction, &ctx.to_account_infos(), ctx.signer_seeds).map_err(Into::into)
+}
+
+pub fn aggregator_set_min_jobs<'info>(
+    ctx: CpiContext<'_, '_, '_, 'info, accounts::AggregatorSetMinJobs<'info>>,
+    params: AggregatorSetMinJobsParams,
+) -> anchor_lang::Result<()> {
+    let instruction = ctx.accounts.get_instruction(*ctx.program.key, params)?;
+    invoke_signed(&instruction, &ctx.to_account_infos(), ctx.signer_seeds).map_err(Into::into)
+}
+
+pub fn aggregator_set_min_oracles<'info>(
+    ctx: CpiContext<'_, '_, '_, 'info, accounts::AggregatorSetMinOracles<'info>>,
+    params: AggregatorSetMinOraclesParams,
+) -> anchor_lang::Result<()> {
+    let instruction = ctx.accounts.get_instruction(*ctx.program.key, params)?;
+    invoke_signed(&instruction, &ctx.to_account_infos(), ctx.signer_seeds).map_err(Into::into)
+}
+
+pub fn aggregator_set_batch_size<'info>(
+    ctx: CpiContext<'_, '_, '_, 'info, accounts::AggregatorSetBatchSize<'info>>,
+    params: AggregatorSetBatchSizeParams,
+) -> anchor_lang::Result<()> {
+    let instruction = ctx.accounts.get_instruction(*ctx.program.key, params)?;
+    invoke_signed(&instruction, &ctx.to_account_infos(), ctx.signer_seeds).map_err(Into::into)
+}
+
+pub fn aggregator_set_update_interval<'info>(
+    ctx: CpiContext<'_, '_, '_, 'info, accounts::AggregatorSetUpdateInterval<'info>>,
+    params: AggregatorSetUpdateIntervalParams,
+) -> anchor_lang::Result<()> {
+    let instruction = ctx.accounts.get_instruction(*ctx.program.key, params)?;
+    invoke_signed(&instruction, &ctx.to_account_infos(), ctx.signer_seeds).map_err(Into::into)
+}
+
+pub fn aggregator_add_job<'info>(
+    ctx: CpiContext<'_, '_, '_, 'info, accounts::AggregatorAddJob<'info>>,
+    params: AggregatorAddJobParams,
+) -> anchor_lang::Result<()> {
+    let instruction = ctx.accounts.get_instruction(*ctx.program.key, params)?;
+    invoke_signed(&instruction, &ctx.to_account_infos(), ctx.signer_seeds).map_err(Into::into)
+}
+
+pub fn aggregator_remove_job<'info>(
+    ctx: CpiContext<'_, '_, '_, 'info, accounts::AggregatorRemoveJob<'info>>,
+    params: AggregatorRemoveJobParams,
+) -> anchor_lang::Result<()> {
+    let instruction = ctx.accounts.get_instruction(*ctx.program.key, params)?;
+    invoke_signed(&instruction, &ctx.to_account_infos(), ctx.signer_seeds).map_err(Into::into)
+}
+
+pub fn aggregator_set_authority<'info>(
+    ctx: CpiContext<'_, '_, '_, 'info, accounts::AggregatorSetAuthority<'info>>,
+    params: AggregatorSetAuthorityParams,
+) -> anchor_lang::Result<()> {
+    let instruction = ctx.accounts.get_instruction(*ctx.program.key, params)?;
+    invoke_signed(&instruction, &ctx.to_account_infos(), ctx.signer_seeds).map_err(Into::into)
+}
+
+pub fn aggregator_lock<'info>(
+    ctx: CpiContext<'_, '_, '_, 'info, accounts::AggregatorLock<'info>>,
+    params: AggregatorLockParams,
+) -> anchor_lang::Result<()> {
+    let instruction = ctx.accounts.get_instruction(*ctx.program.key, params)?;
+    invoke_signed(&instruction, &ctx.to_account_infos(), ctx.signer_seeds).map_err(Into::into)
+}
+
+/// `ctx.remaining_accounts` are the job author wallets matching `params.wallet_bumps`.
+pub fn lease_init<'info>(
+    ctx: CpiContext<'_, '_, '_, 'info, accounts::LeaseInit<'info>>,
+    params: LeaseInitParams,
+) -> anchor_lang::Result<()> {
+    let instruction =
+        ctx.accounts
+            .get_instruction(*ctx.program.key, params, &ctx.remaining_accounts)?;
+    invoke_signed(&instruction, &ctx.to_account_infos(), ctx.signer_seeds).map_err(Into::into)
+}
+
+/// `ctx.remaining_accounts` are the job author wallets matching `params.wallet_bumps`.
+pub fn lease_extend<'info>(
+    ctx: CpiContext<'_, '_, '_, 'info, accounts::LeaseExtend<'info>>,
+    params: LeaseExtendParams,
+) -> anchor_lang::Result<()> {
+    let instruction =
+        ctx.accounts
+            .get_instruction(*ctx.program.key, params, &ctx.remaining_accounts)?;
+    invoke_signed(&instruction, &ctx.to_account_infos(), ctx.signer_seeds).map_err(Into::into)
+}
+
+pub fn lease_withdraw<'info>(
+    ctx: CpiContext<'_, '_, '_, 'info, accounts::LeaseWithdraw<'info>>,
+    params: LeaseWithdrawParams,
+) -> anchor_lang::Result<()> {
+    let instruction = ctx.accounts.get_instruction(*ctx.program.key, params)?;
+    invoke_signed(&instruction, &ctx.to_account_infos(), ctx.signer_seeds).map_err(Into::into)
+}
+
+pub fn permission_init<'info>(
+    ctx: CpiContext<'_, '_, '_, 'info, accounts::PermissionInit<'info>>,
+    params: PermissionInitParams,
+) -> anchor_lang::Result<()> {
+    let instruction = ctx.accounts.get_instruction(*ctx.program.key, params)?;
+    invoke_signed(&instruction, &ctx.to_account_infos(), ctx.signer_seeds).map_err(Into::into)
+}
+
+pub fn permission_set<'info>(
+    ctx: CpiContext<'_, '_, '_, 'info, accounts::PermissionSet<'info>>,
+    params: PermissionSetParams,
+) -> anchor_lang::Result<()> {
+    let instruction = ctx.accounts.get_instruction(*ctx.program.key, params)?;
+    invoke_signed(&instruction, &ctx.to_account_infos(), ctx.signer_seeds).map_err(Into::into)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::error::SwitchboardError;
+    use crate::instruction::tests::TestAccounts;
+    use solana_program::instruction::Instruction;
+    use solana_program::program_stubs::{set_syscall_stubs, SyscallStubs};
+    use std::sync::{Arc, Mutex};
+
+    // (instruction, account info keys, signer seeds) of every invoke
+    type Invokes = Arc<Mutex<Vec<(Instruction, Vec<Pubkey>, Vec<Vec<Vec<u8>>>)>>>;
+
+    // Records invokes instead of running them, the runtime is not available off-chain.
+    struct RecordInvokes(Invokes);
+
+    impl SyscallStubs for RecordInvokes {
+        fn sol_invoke_signed(
+            &self,
+            instruction: &Instruction,
+            account_infos: &[AccountInfo],
+            signers_seeds: &[&[&[u8]]],
+        ) -> solana_program::entrypoint::ProgramResult {
+            self.0.lock().unwrap().push((
+                instruction.clone(),
+                account_infos.iter().map(|info| *info.key).collect(),
+                signers_seeds
+                    .iter()
+                    .map(|seeds| seeds.iter().map(|seed| seed.to_vec()).collect())
+                    .collect(),
+            ));
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_lease_init_cpi() {
+        let invokes = Invokes::default();
+        set_syscall_stubs(Box::new(RecordInvokes(invokes.clone())));
+
+        // lease init accounts, two job wallets and the switchboard program
+        let mut test_accounts = TestAccounts::new(14, &[3, 8, 11, 12], 7);
+        let keys = test_accounts.keys.clone();
+        let infos = test_accounts.infos(&[2, 5, 6, 9, 10, 13]);
+        let lease_init_accounts = || accounts::LeaseInit {
+            lease: infos[0].clone(),
+            queue: infos[1].clone(),
+            aggregator: infos[2].clone(),
+            funder: Account::try_from(&infos[3]).unwrap(),
+            payer: infos[4].clone(),
+            system_program: infos[5].clone(),
+            token_program: infos[6].clone(),
+            owner: infos[7].clone(),
+            escrow: Account::try_from(&infos[8]).unwrap(),
+            program_state: infos[9].clone(),
+            mint: infos[10].clone(),
+        };
+        let params = LeaseInitParams {
+            load_amount: 1,
+            withdraw_authority: keys[7],
+            lease_bump: 2,
+            state_bump: 3,
+            wallet_bumps: vec![4, 5],
+        };
+        let seeds: &[&[u8]] = &[b"owner", &[6]];
+        let signer = &[seeds];
+
+        // the job wallets have to be passed as remaining accounts
+        let ctx = CpiContext::new(infos[13].clone(), lease_init_accounts()).with_signer(signer);
+        assert_eq!(
+            lease_init(ctx, params.clone()).unwrap_err(),
+            error!(SwitchboardError::LeaseJobWalletsMismatch)
+        );
+        let ctx = CpiContext::new(infos[13].clone(), lease_init_accounts())
+            .with_signer(signer)
+            .with_remaining_accounts(infos[11..13].to_vec());
+        lease_init(ctx, params.clone()).unwrap();
+
+        let invokes = invokes.lock().unwrap();
+        assert_eq!(invokes.len(), 1);
+        let (ix, account_keys, signer_seeds) = &invokes[0];
+        assert_eq!(ix.program_id, keys[13]);
+        let mut data = accounts::LeaseInit::discriminator().to_vec();
+        data.extend(params.try_to_vec().unwrap());
+        assert_eq!(ix.data, data);
+        // the accounts struct in order, then the job wallets as writable accounts
+        assert_eq!(
+            ix.accounts
+                .iter()
+                .map(|meta| meta.pubkey)
+                .collect::<Vec<_>>(),
+            keys[..13]
+        );
+        assert!(ix.accounts[4].is_signer && ix.accounts[7].is_signer);
+        assert!(ix.accounts[11..].iter().all(|meta| meta.is_writable));
+        // every account the instruction references is passed to the invoke
+        assert!(ix
+            .accounts
+            .iter()
+            .all(|meta| account_keys.contains(&meta.pubkey)));
+        assert_eq!(signer_seeds, &vec![vec![b"owner".to_vec(), vec![6]]]);
+    }
+}
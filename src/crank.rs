@@ -0,0 +1,145 @@
+use super::error::SwitchboardError;
+use anchor_lang::prelude::*;
+use bytemuck::{Pod, Zeroable};
+use solana_program::pubkey::Pubkey;
+use std::cell::Ref;
+
+#[zero_copy]
+#[repr(C, packed)]
+#[derive(Default, Debug, PartialEq, Eq, Pod, Zeroable)]
+pub struct CrankRow {
+    // Aggregator scheduled on the crank.
+    pub pubkey: Pubkey,
+    // Unix timestamp the aggregator can next be popped at.
+    pub next_timestamp: i64,
+}
+
+#[zero_copy]
+#[repr(C, packed)]
+#[derive(Pod, Zeroable)]
+pub struct CrankAccountData {
+    pub name: [u8; 32],
+    pub metadata: [u8; 64],
+    pub queue_pubkey: Pubkey,
+    // Number of rows currently in the data buffer.
+    pub pq_size: u32,
+    pub max_rows: u32,
+    pub jitter_modifier: u8,
+    pub _ebuf: [u8; 255], // Buffer for future info
+    pub data_buffer: Pubkey,
+}
+
+#[derive(Clone, Debug, AnchorSerialize, AnchorDeserialize)]
+pub struct CrankPushParams {
+    pub state_bump: u8,
+    pub permission_bump: u8,
+}
+
+#[derive(Clone, Debug, AnchorSerialize, AnchorDeserialize)]
+pub struct CrankPopParams {
+    pub state_bump: u8,
+    pub lease_bumps: Vec<u8>,
+    pub permission_bumps: Vec<u8>,
+    pub nonce: Option<u32>,
+    pub fail_open_on_account_mismatch: Option<bool>,
+}
+
+// 8 byte "BUFFERxx" discriminator ahead of the crank rows
+const CRANK_BUFFER_HEADER_SIZE: usize = 8;
+
+impl CrankAccountData {
+    pub fn new<'info>(
+        crank: &'info AccountInfo,
+    ) -> anchor_lang::Result<Ref<'info, CrankAccountData>> {
+        let data = crank.try_borrow_data()?;
+
+        let mut disc_bytes = [0u8; 8];
+        disc_bytes.copy_from_slice(&data[..8]);
+        if disc_bytes != CrankAccountData::discriminator() {
+            msg!("{:?}", disc_bytes);
+            return Err(error!(SwitchboardError::AccountDiscriminatorMismatch));
+        }
+
+        Ok(Ref::map(data, |data| {
+            bytemuck::from_bytes(&data[8..8 + std::mem::size_of::<CrankAccountData>()])
+        }))
+    }
+
+    /// Rows currently scheduled on the crank, decoded from the crank's data buffer account data.
+    pub fn rows<'a>(&self, data_buffer: &'a [u8]) -> anchor_lang::Result<&'a [CrankRow]> {
+        if data_buffer.len() < CRANK_BUFFER_HEADER_SIZE {
+            return Err(error!(SwitchboardError::CrankBufferTooSmall));
+        }
+        if data_buffer[..8] != *b"BUFFERxx" {
+            return Err(error!(SwitchboardError::AccountDiscriminatorMismatch));
+        }
+        let end =
+            CRANK_BUFFER_HEADER_SIZE + self.pq_size as usize * std::mem::size_of::<CrankRow>();
+        if data_buffer.len() < end {
+            return Err(error!(SwitchboardError::CrankBufferTooSmall));
+        }
+        Ok(bytemuck::cast_slice(
+            &data_buffer[CRANK_BUFFER_HEADER_SIZE..end],
+        ))
+    }
+
+    /// Up to `limit` rows ready to pop at `now`, soonest first.
+    pub fn ready_rows(rows: &[CrankRow], now: i64, limit: usize) -> Vec<CrankRow> {
+        let mut ready: Vec<CrankRow> = rows
+            .iter()
+            .filter(|row| row.next_timestamp <= now)
+            .copied()
+            .collect();
+        ready.sort_by_key(|row| row.next_timestamp);
+        ready.truncate(limit);
+        ready
+    }
+
+    fn discriminator() -> [u8; 8] {
+        [111, 81, 146, 73, 172, 180, 134, 209]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_crank_rows() {
+        let mut crank = CrankAccountData::zeroed();
+        crank.pq_size = 3;
+        let rows = [
+            CrankRow {
+                pubkey: Pubkey::new_unique(),
+                next_timestamp: 300,
+            },
+            CrankRow {
+                pubkey: Pubkey::new_unique(),
+                next_timestamp: 100,
+            },
+            CrankRow {
+                pubkey: Pubkey::new_unique(),
+                next_timestamp: 200,
+            },
+            // beyond pq_size, left over from a popped row
+            CrankRow {
+                pubkey: Pubkey::new_unique(),
+                next_timestamp: 0,
+            },
+        ];
+        let mut buffer = b"BUFFERxx".to_vec();
+        buffer.extend_from_slice(bytemuck::cast_slice(&rows));
+
+        let decoded = crank.rows(&buffer).unwrap();
+        assert_eq!(decoded, &rows[..3]);
+        let ready = CrankAccountData::ready_rows(decoded, 250, 5);
+        assert_eq!(ready, vec![rows[1], rows[2]]);
+        assert_eq!(CrankAccountData::ready_rows(decoded, 250, 1), vec![rows[1]]);
+
+        crank.pq_size = 5;
+        assert!(crank.rows(&buffer).is_err());
+        buffer[0] = 0;
+        crank.pq_size = 1;
+        assert!(crank.rows(&buffer).is_err());
+    }
+}
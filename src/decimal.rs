@@ -1,13 +1,14 @@
 use super::error::SwitchboardError;
 use anchor_lang::prelude::*;
+use bytemuck::{Pod, Zeroable};
 use core::cmp::Ordering;
 use rust_decimal::prelude::{FromPrimitive, ToPrimitive};
 use rust_decimal::Decimal;
 use std::convert::{From, TryInto};
 
 #[zero_copy]
-#[repr(packed)]
-#[derive(Default, Debug, Eq, PartialEq)]
+#[repr(C, packed)]
+#[derive(Default, Debug, Eq, PartialEq, Pod, Zeroable)]
 pub struct SwitchboardDecimal {
     pub mantissa: i128,
     pub scale: u32,
@@ -25,6 +26,25 @@ impl SwitchboardDecimal {
         Self::from_rust_decimal(dec)
     }
 }
+/// Borsh encoded decimal used in instruction parameters, e.g. an aggregator variance threshold.
+#[derive(Default, Debug, Copy, Clone, Eq, PartialEq, AnchorSerialize, AnchorDeserialize)]
+pub struct BorshDecimal {
+    pub mantissa: i128,
+    pub scale: u32,
+}
+impl From<SwitchboardDecimal> for BorshDecimal {
+    fn from(decimal: SwitchboardDecimal) -> Self {
+        Self {
+            mantissa: decimal.mantissa,
+            scale: decimal.scale,
+        }
+    }
+}
+impl From<BorshDecimal> for SwitchboardDecimal {
+    fn from(decimal: BorshDecimal) -> Self {
+        Self::new(decimal.mantissa, decimal.scale)
+    }
+}
 impl TryInto<Decimal> for &SwitchboardDecimal {
     type Error = anchor_lang::error::Error;
     fn try_into(self) -> anchor_lang::Result<Decimal> {
@@ -0,0 +1,437 @@
+//! Decode instructions sent to the Switchboard program, e.g. for explorers and audit logs.
+//!
+//! ```ignore
+//! let decoded = DecodedInstruction::from_instruction(&instruction)?;
+//! println!("{} by {:?}", decoded.instruction.name(), decoded.account("authority"));
+//! ```
+use super::aggregator::{
+    AggregatorInitParams, AggregatorOpenRound, AggregatorOpenRoundParams,
+    AggregatorSaveResultParams, JobInitParams,
+};
+use super::aggregator_config::*;
+use super::crank::{CrankPopParams, CrankPushParams};
+use super::error::SwitchboardError;
+use super::instruction::*;
+use super::lease::{
+    LeaseExtend, LeaseExtendParams, LeaseInit, LeaseInitParams, LeaseWithdraw, LeaseWithdrawParams,
+};
+use super::oracle_queue::OracleHeartbeatParams;
+use super::permission::{PermissionInit, PermissionInitParams, PermissionSet, PermissionSetParams};
+use super::vrf::{
+    VrfInit, VrfInitParams, VrfRequestRandomness, VrfRequestRandomnessParams, VrfSetCallback,
+    VrfSetCallbackParams,
+};
+use anchor_lang::prelude::*;
+use solana_program::instruction::Instruction;
+
+// Label for accounts past the instruction's own, e.g. crank pop rows or lease job wallets.
+const REMAINING_ACCOUNT: &str = "remaining";
+
+/// A known Switchboard instruction with its decoded params.
+#[derive(Clone, Debug)]
+pub enum SwitchboardInstruction {
+    AggregatorInit(AggregatorInitParams),
+    AggregatorOpenRound(AggregatorOpenRoundParams),
+    AggregatorSaveResult(AggregatorSaveResultParams),
+    AggregatorSetVarianceThreshold(AggregatorSetVarianceThresholdParams),
+    AggregatorSetMinJobs(AggregatorSetMinJobsParams),
+    AggregatorSetMinOracles(AggregatorSetMinOraclesParams),
+    AggregatorSetBatchSize(AggregatorSetBatchSizeParams),
+    AggregatorSetUpdateInterval(AggregatorSetUpdateIntervalParams),
+    AggregatorAddJob(AggregatorAddJobParams),
+    AggregatorRemoveJob(AggregatorRemoveJobParams),
+    AggregatorSetAuthority(AggregatorSetAuthorityParams),
+    AggregatorLock(AggregatorLockParams),
+    JobInit(JobInitParams),
+    LeaseInit(LeaseInitParams),
+    LeaseExtend(LeaseExtendParams),
+    LeaseWithdraw(LeaseWithdrawParams),
+    PermissionInit(PermissionInitParams),
+    PermissionSet(PermissionSetParams),
+    CrankPush(CrankPushParams),
+    CrankPop(CrankPopParams),
+    OracleHeartbeat(OracleHeartbeatParams),
+    VrfInit(VrfInitParams),
+    VrfRequestRandomness(VrfRequestRandomnessParams),
+    VrfSetCallback(VrfSetCallbackParams),
+}
+
+impl SwitchboardInstruction {
+    /// Decode instruction data, an 8 byte discriminator followed by the borsh encoded params.
+    pub fn decode(data: &[u8]) -> anchor_lang::Result<SwitchboardInstruction> {
+        if data.len() < 8 {
+            return Err(error!(SwitchboardError::InstructionDataError));
+        }
+        let (discriminator, params) = data.split_at(8);
+
+        let instruction = if discriminator == AGGREGATOR_INIT_DISCRIMINATOR {
+            SwitchboardInstruction::AggregatorInit(decode_params(params)?)
+        } else if discriminator == AggregatorOpenRound::discriminator() {
+            SwitchboardInstruction::AggregatorOpenRound(decode_params(params)?)
+        } else if discriminator == AGGREGATOR_SAVE_RESULT_DISCRIMINATOR {
+            SwitchboardInstruction::AggregatorSaveResult(decode_params(params)?)
+        } else if discriminator == AggregatorSetVarianceThreshold::discriminator() {
+            SwitchboardInstruction::AggregatorSetVarianceThreshold(decode_params(params)?)
+        } else if discriminator == AggregatorSetMinJobs::discriminator() {
+            SwitchboardInstruction::AggregatorSetMinJobs(decode_params(params)?)
+        } else if discriminator == AggregatorSetMinOracles::discriminator() {
+            SwitchboardInstruction::AggregatorSetMinOracles(decode_params(params)?)
+        } else if discriminator == AggregatorSetBatchSize::discriminator() {
+            SwitchboardInstruction::AggregatorSetBatchSize(decode_params(params)?)
+        } else if discriminator == AggregatorSetUpdateInterval::discriminator() {
+            SwitchboardInstruction::AggregatorSetUpdateInterval(decode_params(params)?)
+        } else if discriminator == AggregatorAddJob::discriminator() {
+            SwitchboardInstruction::AggregatorAddJob(decode_params(params)?)
+        } else if discriminator == AggregatorRemoveJob::discriminator() {
+            SwitchboardInstruction::AggregatorRemoveJob(decode_params(params)?)
+        } else if discriminator == AggregatorSetAuthority::discriminator() {
+            SwitchboardInstruction::AggregatorSetAuthority(decode_params(params)?)
+        } else if discriminator == AggregatorLock::discriminator() {
+            SwitchboardInstruction::AggregatorLock(decode_params(params)?)
+        } else if discriminator == JOB_INIT_DISCRIMINATOR {
+            SwitchboardInstruction::JobInit(decode_params(params)?)
+        } else if discriminator == LeaseInit::discriminator() {
+            SwitchboardInstruction::LeaseInit(decode_params(params)?)
+        } else if discriminator == LeaseExtend::discriminator() {
+            SwitchboardInstruction::LeaseExtend(decode_params(params)?)
+        } else if discriminator == LeaseWithdraw::discriminator() {
+            SwitchboardInstruction::LeaseWithdraw(decode_params(params)?)
+        } else if discriminator == PermissionInit::discriminator() {
+            SwitchboardInstruction::PermissionInit(decode_params(params)?)
+        } else if discriminator == PermissionSet::discriminator() {
+            SwitchboardInstruction::PermissionSet(decode_params(params)?)
+        } else if discriminator == CRANK_PUSH_DISCRIMINATOR {
+            SwitchboardInstruction::CrankPush(decode_params(params)?)
+        } else if discriminator == CRANK_POP_DISCRIMINATOR {
+            SwitchboardInstruction::CrankPop(decode_params(params)?)
+        } else if discriminator == ORACLE_HEARTBEAT_DISCRIMINATOR {
+            SwitchboardInstruction::OracleHeartbeat(decode_params(params)?)
+        } else if discriminator == VrfInit::discriminator() {
+            SwitchboardInstruction::VrfInit(decode_params(params)?)
+        } else if discriminator == VrfRequestRandomness::discriminator() {
+            SwitchboardInstruction::VrfRequestRandomness(decode_params(params)?)
+        } else if discriminator == VrfSetCallback::discriminator() {
+            SwitchboardInstruction::VrfSetCallback(decode_params(params)?)
+        } else {
+            return Err(error!(SwitchboardError::UnknownInstructionError));
+        };
+        Ok(instruction)
+    }
+
+    /// The instruction's name in the Switchboard program, e.g. "vrf_request_randomness".
+    pub fn name(&self) -> &'static str {
+        match self {
+            SwitchboardInstruction::AggregatorInit(_) => "aggregator_init",
+            SwitchboardInstruction::AggregatorOpenRound(_) => "aggregator_open_round",
+            SwitchboardInstruction::AggregatorSaveResult(_) => "aggregator_save_result",
+            SwitchboardInstruction::AggregatorSetVarianceThreshold(_) => {
+                "aggregator_set_variance_threshold"
+            }
+            SwitchboardInstruction::AggregatorSetMinJobs(_) => "aggregator_set_min_jobs",
+            SwitchboardInstruction::AggregatorSetMinOracles(_) => "aggregator_set_min_oracles",
+            SwitchboardInstruction::AggregatorSetBatchSize(_) => "aggregator_set_batch_size",
+            SwitchboardInstruction::AggregatorSetUpdateInterval(_) => {
+                "aggregator_set_update_interval"
+            }
+            SwitchboardInstruction::AggregatorAddJob(_) => "aggregator_add_job",
+            SwitchboardInstruction::AggregatorRemoveJob(_) => "aggregator_remove_job",
+            SwitchboardInstruction::AggregatorSetAuthority(_) => "aggregator_set_authority",
+            SwitchboardInstruction::AggregatorLock(_) => "aggregator_lock",
+            SwitchboardInstruction::JobInit(_) => "job_init",
+            SwitchboardInstruction::LeaseInit(_) => "lease_init",
+            SwitchboardInstruction::LeaseExtend(_) => "lease_extend",
+            SwitchboardInstruction::LeaseWithdraw(_) => "lease_withdraw",
+            SwitchboardInstruction::PermissionInit(_) => "permission_init",
+            SwitchboardInstruction::PermissionSet(_) => "permission_set",
+            SwitchboardInstruction::CrankPush(_) => "crank_push",
+            SwitchboardInstruction::CrankPop(_) => "crank_pop",
+            SwitchboardInstruction::OracleHeartbeat(_) => "oracle_heartbeat",
+            SwitchboardInstruction::VrfInit(_) => "vrf_init",
+            SwitchboardInstruction::VrfRequestRandomness(_) => "vrf_request_randomness",
+            SwitchboardInstruction::VrfSetCallback(_) => "vrf_set_callback",
+        }
+    }
+
+    /// Names of the instruction's accounts, in the order the program expects them.
+    pub fn account_names(&self) -> &'static [&'static str] {
+        match self {
+            SwitchboardInstruction::AggregatorInit(_) => &[
+                "aggregator",
+                "authority",
+                "queue",
+                "author_wallet",
+                "program_state",
+            ],
+            SwitchboardInstruction::AggregatorOpenRound(_) => &[
+                "aggregator",
+                "lease",
+                "oracle_queue",
+                "queue_authority",
+                "permission",
+                "escrow",
+                "program_state",
+                "payout_wallet",
+                "token_program",
+                "data_buffer",
+                "mint",
+            ],
+            SwitchboardInstruction::AggregatorSaveResult(_) => &[
+                "aggregator",
+                "oracle",
+                "oracle_authority",
+                "oracle_queue",
+                "queue_authority",
+                "feed_permission",
+                "oracle_permission",
+                "lease",
+                "escrow",
+                "token_program",
+                "program_state",
+                "history_buffer",
+                "mint",
+            ],
+            SwitchboardInstruction::AggregatorSetVarianceThreshold(_)
+            | SwitchboardInstruction::AggregatorSetMinJobs(_)
+            | SwitchboardInstruction::AggregatorSetMinOracles(_)
+            | SwitchboardInstruction::AggregatorSetBatchSize(_)
+            | SwitchboardInstruction::AggregatorSetUpdateInterval(_)
+            | SwitchboardInstruction::AggregatorLock(_) => &["aggregator", "authority"],
+            SwitchboardInstruction::AggregatorAddJob(_)
+            | SwitchboardInstruction::AggregatorRemoveJob(_) => &["aggregator", "authority", "job"],
+            SwitchboardInstruction::AggregatorSetAuthority(_) => {
+                &["aggregator", "authority", "new_authority"]
+            }
+            SwitchboardInstruction::JobInit(_) => &["job", "author_wallet", "program_state"],
+            SwitchboardInstruction::LeaseInit(_) => &[
+                "lease",
+                "queue",
+                "aggregator",
+                "funder",
+                "payer",
+                "system_program",
+                "token_program",
+                "owner",
+                "escrow",
+                "program_state",
+                "mint",
+            ],
+            SwitchboardInstruction::LeaseExtend(_) => &[
+                "lease",
+                "aggregator",
+                "queue",
+                "funder",
+                "owner",
+                "escrow",
+                "token_program",
+                "program_state",
+                "mint",
+            ],
+            SwitchboardInstruction::LeaseWithdraw(_) => &[
+                "lease",
+                "escrow",
+                "aggregator",
+                "queue",
+                "withdraw_authority",
+                "withdraw_account",
+                "token_program",
+                "program_state",
+                "mint",
+            ],
+            SwitchboardInstruction::PermissionInit(_) => &[
+                "permission",
+                "authority",
+                "granter",
+                "grantee",
+                "payer",
+                "system_program",
+            ],
+            SwitchboardInstruction::PermissionSet(_) => &["permission", "authority"],
+            SwitchboardInstruction::CrankPush(_) => &[
+                "crank",
+                "aggregator",
+                "oracle_queue",
+                "queue_authority",
+                "permission",
+                "lease",
+                "escrow",
+                "program_state",
+                "data_buffer",
+            ],
+            SwitchboardInstruction::CrankPop(_) => &[
+                "crank",
+                "oracle_queue",
+                "queue_authority",
+                "program_state",
+                "payout_wallet",
+                "token_program",
+                "crank_data_buffer",
+                "queue_data_buffer",
+                "mint",
+            ],
+            SwitchboardInstruction::OracleHeartbeat(_) => &[
+                "oracle",
+                "oracle_authority",
+                "token_account",
+                "gc_oracle",
+                "oracle_queue",
+                "permission",
+                "data_buffer",
+            ],
+            SwitchboardInstruction::VrfInit(_) => &[
+                "vrf",
+                "authority",
+                "oracle_queue",
+                "escrow",
+                "program_state",
+                "token_program",
+            ],
+            SwitchboardInstruction::VrfRequestRandomness(_) => &[
+                "authority",
+                "vrf",
+                "oracle_queue",
+                "queue_authority",
+                "data_buffer",
+                "permission",
+                "escrow",
+                "payer_wallet",
+                "payer_authority",
+                "recent_blockhashes",
+                "program_state",
+                "token_program",
+            ],
+            SwitchboardInstruction::VrfSetCallback(_) => &["vrf", "authority"],
+        }
+    }
+}
+
+/// An instruction account with the name the program gives it.
+#[derive(Clone, Debug)]
+pub struct LabeledAccount {
+    pub name: &'static str,
+    pub meta: AccountMeta,
+}
+
+/// A decoded instruction along with its labeled accounts.
+#[derive(Clone, Debug)]
+pub struct DecodedInstruction {
+    pub instruction: SwitchboardInstruction,
+    pub accounts: Vec<LabeledAccount>,
+}
+
+impl DecodedInstruction {
+    /// Decode instruction data and label its accounts. Accounts past the instruction's own are
+    /// labeled "remaining".
+    pub fn decode(
+        data: &[u8],
+        accounts: &[AccountMeta],
+    ) -> anchor_lang::Result<DecodedInstruction> {
+        let instruction = SwitchboardInstruction::decode(data)?;
+        let names = instruction.account_names();
+        if accounts.len() < names.len() {
+            return Err(error!(SwitchboardError::InstructionAccountsMissing));
+        }
+        let accounts = accounts
+            .iter()
+            .enumerate()
+            .map(|(idx, meta)| LabeledAccount {
+                name: names.get(idx).copied().unwrap_or(REMAINING_ACCOUNT),
+                meta: meta.clone(),
+            })
+            .collect();
+        Ok(DecodedInstruction {
+            instruction,
+            accounts,
+        })
+    }
+
+    /// Decode an instruction sent to the Switchboard program. The program id is not checked.
+    pub fn from_instruction(instruction: &Instruction) -> anchor_lang::Result<DecodedInstruction> {
+        DecodedInstruction::decode(&instruction.data, &instruction.accounts)
+    }
+
+    /// The first account with the given label.
+    pub fn account(&self, name: &str) -> Option<&AccountMeta> {
+        self.accounts
+            .iter()
+            .find(|account| account.name == name)
+            .map(|account| &account.meta)
+    }
+
+    /// Accounts past the instruction's own, e.g. crank pop rows or lease job wallets.
+    pub fn remaining_accounts(&self) -> &[LabeledAccount] {
+        let len = self.instruction.account_names().len();
+        &self.accounts[len..]
+    }
+}
+
+// Like the program, ignores any bytes past the params.
+fn decode_params<T: AnchorDeserialize>(mut params: &[u8]) -> anchor_lang::Result<T> {
+    T::deserialize(&mut params).map_err(|_| error!(SwitchboardError::InstructionDataError))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::permission::SwitchboardPermission;
+
+    #[test]
+    fn test_decode_instruction() {
+        let permission = Pubkey::new_unique();
+        let authority = Pubkey::new_unique();
+        let mut data = PermissionSet::discriminator().to_vec();
+        data.extend_from_slice(&[2, 1]);
+        let instruction = Instruction::new_with_bytes(
+            crate::SWITCHBOARD_V2_DEVNET,
+            &data,
+            vec![
+                AccountMeta::new(permission, false),
+                AccountMeta::new_readonly(authority, true),
+            ],
+        );
+
+        let decoded = DecodedInstruction::from_instruction(&instruction).unwrap();
+        assert_eq!(decoded.instruction.name(), "permission_set");
+        match &decoded.instruction {
+            SwitchboardInstruction::PermissionSet(params) => {
+                assert_eq!(params.permission, SwitchboardPermission::PermitVrfRequests);
+                assert!(params.enable);
+            }
+            other => panic!("unexpected instruction {:?}", other),
+        }
+        assert_eq!(decoded.account("authority").unwrap().pubkey, authority);
+        assert!(decoded.remaining_accounts().is_empty());
+
+        // crank pop rows follow the instruction's own accounts
+        let mut data = CRANK_POP_DISCRIMINATOR.to_vec();
+        data.extend(
+            CrankPopParams {
+                state_bump: 255,
+                lease_bumps: vec![1],
+                permission_bumps: vec![2],
+                nonce: None,
+                fail_open_on_account_mismatch: None,
+            }
+            .try_to_vec()
+            .unwrap(),
+        );
+        let accounts: Vec<AccountMeta> = (0..11)
+            .map(|_| AccountMeta::new(Pubkey::new_unique(), false))
+            .collect();
+        let decoded = DecodedInstruction::decode(&data, &accounts).unwrap();
+        assert_eq!(decoded.accounts[8].name, "mint");
+        assert_eq!(decoded.remaining_accounts().len(), 2);
+        assert_eq!(decoded.remaining_accounts()[0].name, "remaining");
+
+        assert!(DecodedInstruction::decode(&data, &accounts[..8]).is_err());
+        assert!(SwitchboardInstruction::decode(&data[..9]).is_err());
+        // anchor ignores trailing bytes, so they decode to the same params
+        let mut trailing = data.clone();
+        trailing.push(0);
+        match SwitchboardInstruction::decode(&trailing).unwrap() {
+            SwitchboardInstruction::CrankPop(params) => assert_eq!(params.lease_bumps, vec![1]),
+            other => panic!("unexpected instruction {:?}", other),
+        }
+        assert!(SwitchboardInstruction::decode(&[0u8; 16]).is_err());
+        assert!(SwitchboardInstruction::decode(&[1, 2, 3]).is_err());
+    }
+}
@@ -0,0 +1,394 @@
+//! Off-chain verification of the ECVRF proofs oracles submit when fulfilling a VRF request.
+//!
+//! Proofs are verified the same way the Switchboard program does on-chain: over the Ristretto
+//! group with SHA-512, using the oracle's ed25519 key as the public key. The program's output is
+//! the SHA-256 of the proof's gamma point as it stores it, see `VrfBuilder::output`.
+use super::error::SwitchboardError;
+use super::vrf::{EdwardsPointZC, VrfAccountData, VrfBuilder};
+use anchor_lang::prelude::*;
+use curve25519_dalek::constants::RISTRETTO_BASEPOINT_POINT;
+use curve25519_dalek::edwards::CompressedEdwardsY;
+use curve25519_dalek::ristretto::{CompressedRistretto, RistrettoPoint};
+use curve25519_dalek::scalar::Scalar;
+use sha2::{Digest, Sha256, Sha512};
+
+/// Suite string the Switchboard program prefixes to every ECVRF hash.
+pub const SUITE: u8 = 5;
+
+/// A decoded 80 byte ECVRF proof.
+#[derive(Copy, Clone, Debug)]
+pub struct EcvrfProof {
+    pub gamma: RistrettoPoint,
+    // 16 byte challenge, zero extended to a scalar
+    pub c: Scalar,
+    pub s: Scalar,
+}
+
+impl EcvrfProof {
+    /// decode a proof laid out as the compressed gamma point, the 16 byte challenge and the s
+    /// scalar, as stored in `VrfBuilder::repr_proof`
+    pub fn from_bytes(proof: &[u8; 80]) -> anchor_lang::Result<EcvrfProof> {
+        let mut gamma = [0u8; 32];
+        gamma.copy_from_slice(&proof[..32]);
+        let gamma = CompressedRistretto(gamma)
+            .decompress()
+            .ok_or(error!(SwitchboardError::InvalidVrfProof))?;
+
+        let mut c = [0u8; 32];
+        c[..16].copy_from_slice(&proof[32..48]);
+        let mut s = [0u8; 32];
+        s.copy_from_slice(&proof[48..]);
+
+        Ok(Self {
+            gamma,
+            c: Scalar::from_bytes_mod_order(c),
+            s: Scalar::from_bytes_mod_order(s),
+        })
+    }
+}
+
+/// Verify a proof over `alpha` under the oracle's VRF public key, returning the decoded proof.
+pub fn verify(
+    public_key: &Pubkey,
+    alpha: &[u8],
+    proof: &[u8; 80],
+) -> anchor_lang::Result<EcvrfProof> {
+    let decoded = EcvrfProof::from_bytes(proof)?;
+    let y = edwards_to_ristretto(public_key).ok_or(error!(SwitchboardError::InvalidVrfProof))?;
+
+    let h = hash_to_curve(public_key, alpha);
+    let u = decoded.s * RISTRETTO_BASEPOINT_POINT - decoded.c * y;
+    let v = decoded.s * h - decoded.c * decoded.gamma;
+    if hash_points(&[h, decoded.gamma, u, v])[..] != proof[32..48] {
+        return Err(error!(SwitchboardError::InvalidVrfProof));
+    }
+    Ok(decoded)
+}
+
+impl VrfBuilder {
+    /// verify this builder's proof over `alpha` under the producing oracle's VRF key
+    pub fn verify_proof(&self, alpha: &[u8]) -> anchor_lang::Result<EcvrfProof> {
+        let public_key = self.Y_point;
+        let proof = self.repr_proof;
+        verify(&public_key, alpha, &proof)
+    }
+
+    /// The 32 byte VRF output the program derives from this builder's proof: the SHA-256 of the
+    /// decompressed gamma point in its stored zero-copy layout. Fails if the stored point is not
+    /// the gamma encoded in `repr_proof`.
+    pub fn output(&self) -> anchor_lang::Result<[u8; 32]> {
+        let gamma = self.proof.Gamma;
+        match field::ristretto_encode(&gamma) {
+            Some(encoded) if encoded[..] == self.repr_proof[..32] => {}
+            _ => return Err(error!(SwitchboardError::InvalidVrfProof)),
+        }
+        let mut output = [0u8; 32];
+        output.copy_from_slice(&Sha256::digest(bytemuck::bytes_of(&gamma)));
+        Ok(output)
+    }
+}
+
+impl VrfAccountData {
+    /// Verify every builder proof for the current round over the round's alpha and recompute the
+    /// round's result from each proof's gamma. Returns the verified result.
+    pub fn verify_current_round(&self) -> anchor_lang::Result<[u8; 32]> {
+        let result = self.get_result()?;
+        let alpha = self.current_round.alpha;
+        let alpha_len = self.current_round.alpha_len as usize;
+        let builders_len = self.builders_len as usize;
+        if alpha_len > alpha.len() || builders_len == 0 || builders_len > self.builders.len() {
+            return Err(error!(SwitchboardError::InvalidVrfProof));
+        }
+
+        for builder in self.builders[..builders_len].iter() {
+            builder.verify_proof(&alpha[..alpha_len])?;
+            let output = builder.output()?;
+            if output != builder.result || output != result {
+                return Err(error!(SwitchboardError::VrfResultMismatch));
+            }
+        }
+        Ok(result)
+    }
+}
+
+// The program uses the oracle's ed25519 key as a ristretto point. curve25519-dalek has no public
+// conversion, so re-encode the torsion free edwards point as ristretto bytes and decode those.
+fn edwards_to_ristretto(public_key: &Pubkey) -> Option<RistrettoPoint> {
+    let bytes = public_key.to_bytes();
+    let point = CompressedEdwardsY(bytes).decompress()?;
+    if !point.is_torsion_free() {
+        return None;
+    }
+    let encoded = field::ristretto_encode_compressed(&bytes)?;
+    CompressedRistretto(encoded).decompress()
+}
+
+fn hash_to_curve(public_key: &Pubkey, alpha: &[u8]) -> RistrettoPoint {
+    let mut msg = vec![SUITE, 1];
+    msg.extend_from_slice(public_key.as_ref());
+    msg.extend_from_slice(alpha);
+    RistrettoPoint::hash_from_bytes::<Sha512>(&msg)
+}
+
+fn hash_points(points: &[RistrettoPoint]) -> [u8; 16] {
+    let mut hasher = Sha512::new();
+    hasher.update([SUITE, 2]);
+    for point in points.iter() {
+        hasher.update(point.compress().as_bytes());
+    }
+    let mut c = [0u8; 16];
+    c.copy_from_slice(&hasher.finalize()[..16]);
+    c
+}
+
+// Ristretto encoding (RFC 9496) of the edwards points the program works with. curve25519-dalek
+// keeps its field type private, so coordinates are plain integers modulo 2^255 - 19.
+mod field {
+    use super::EdwardsPointZC;
+    use num_bigint::BigUint;
+
+    struct Field {
+        p: BigUint,
+        d: BigUint,
+        sqrt_m1: BigUint,
+    }
+
+    impl Field {
+        fn new() -> Field {
+            let p = (BigUint::from(1u32) << 255u32) - 19u32;
+            // edwards d = -121665 / 121666
+            let d = (&p - 121665u32) * BigUint::from(121666u32).modpow(&(&p - 2u32), &p) % &p;
+            let sqrt_m1 = BigUint::from(2u32).modpow(&((&p - 1u32) >> 2u32), &p);
+            Field { p, d, sqrt_m1 }
+        }
+
+        fn decode(&self, bytes: &[u8; 32]) -> BigUint {
+            let mut bytes = *bytes;
+            bytes[31] &= 0x7f;
+            BigUint::from_bytes_le(&bytes) % &self.p
+        }
+
+        // limbs are little endian radix 2^51, as in curve25519-dalek's FieldElement51
+        fn unpack(&self, limbs: [u64; 5]) -> BigUint {
+            limbs.iter().rev().fold(BigUint::from(0u32), |value, limb| {
+                ((value << 51u32) + *limb) % &self.p
+            })
+        }
+
+        fn to_bytes(&self, value: &BigUint) -> [u8; 32] {
+            let mut bytes = [0u8; 32];
+            let le = (value % &self.p).to_bytes_le();
+            bytes[..le.len()].copy_from_slice(&le);
+            bytes
+        }
+
+        fn mul(&self, a: &BigUint, b: &BigUint) -> BigUint {
+            a * b % &self.p
+        }
+
+        fn add(&self, a: &BigUint, b: &BigUint) -> BigUint {
+            (a + b) % &self.p
+        }
+
+        fn neg(&self, a: &BigUint) -> BigUint {
+            (&self.p - a % &self.p) % &self.p
+        }
+
+        fn sub(&self, a: &BigUint, b: &BigUint) -> BigUint {
+            self.add(a, &self.neg(b))
+        }
+
+        fn invert(&self, a: &BigUint) -> BigUint {
+            a.modpow(&(&self.p - 2u32), &self.p)
+        }
+
+        fn is_negative(&self, a: &BigUint) -> bool {
+            self.to_bytes(a)[0] & 1 == 1
+        }
+
+        fn abs(&self, a: &BigUint) -> BigUint {
+            if self.is_negative(a) {
+                self.neg(a)
+            } else {
+                a % &self.p
+            }
+        }
+
+        // SQRT_RATIO_M1: whether u / v is square, and the nonnegative root of u / v or of
+        // i * u / v when it is not
+        fn sqrt_ratio_m1(&self, u: &BigUint, v: &BigUint) -> (bool, BigUint) {
+            let v3 = self.mul(&self.mul(v, v), v);
+            let v7 = self.mul(&self.mul(&v3, &v3), v);
+            let exponent = (&self.p - 5u32) >> 3u32;
+            let r = self.mul(
+                &self.mul(u, &v3),
+                &self.mul(u, &v7).modpow(&exponent, &self.p),
+            );
+            let check = self.mul(v, &self.mul(&r, &r));
+
+            let u = u % &self.p;
+            let correct_sign_sqrt = check == u;
+            let flipped_sign_sqrt = check == self.neg(&u);
+            let flipped_sign_sqrt_i = check == self.mul(&self.neg(&u), &self.sqrt_m1);
+            let r = if flipped_sign_sqrt || flipped_sign_sqrt_i {
+                self.mul(&r, &self.sqrt_m1)
+            } else {
+                r
+            };
+            (correct_sign_sqrt || flipped_sign_sqrt, self.abs(&r))
+        }
+
+        fn on_curve(&self, x: &BigUint, y: &BigUint) -> bool {
+            let xx = self.mul(x, x);
+            let yy = self.mul(y, y);
+            let one = BigUint::from(1u32);
+            // -x^2 + y^2 = 1 + d x^2 y^2
+            self.sub(&yy, &xx) == self.add(&one, &self.mul(&self.d, &self.mul(&xx, &yy)))
+        }
+
+        fn encode(&self, x: &BigUint, y: &BigUint) -> [u8; 32] {
+            let one = BigUint::from(1u32);
+            let t = self.mul(x, y);
+            let u1 = self.mul(&self.add(&one, y), &self.sub(&one, y));
+            let u2 = self.mul(x, y);
+            let (_, invsqrt) = self.sqrt_ratio_m1(&one, &self.mul(&u1, &self.mul(&u2, &u2)));
+            let den1 = self.mul(&invsqrt, &u1);
+            let den2 = self.mul(&invsqrt, &u2);
+            let z_inv = self.mul(&self.mul(&den1, &den2), &t);
+
+            let (x, y, den_inv) = if self.is_negative(&self.mul(&t, &z_inv)) {
+                // 1 / sqrt(a - d) with a = -1
+                let minus_one_minus_d = self.sub(&self.neg(&one), &self.d);
+                let (_, invsqrt_a_minus_d) = self.sqrt_ratio_m1(&one, &minus_one_minus_d);
+                (
+                    self.mul(y, &self.sqrt_m1),
+                    self.mul(x, &self.sqrt_m1),
+                    self.mul(&den1, &invsqrt_a_minus_d),
+                )
+            } else {
+                (x.clone(), y.clone(), den2)
+            };
+            let y = if self.is_negative(&self.mul(&x, &z_inv)) {
+                self.neg(&y)
+            } else {
+                y
+            };
+            self.to_bytes(&self.abs(&self.mul(&den_inv, &self.sub(&one, &y))))
+        }
+    }
+
+    /// Ristretto encoding of a point in the program's zero-copy edwards layout, None if the
+    /// coordinates are not a point on the curve.
+    pub fn ristretto_encode(point: &EdwardsPointZC) -> Option<[u8; 32]> {
+        let field = Field::new();
+        let [x, y, z, t] = [point.X, point.Y, point.Z, point.T].map(|fe| field.unpack(fe.bytes));
+        if z == BigUint::from(0u32) || field.mul(&t, &z) != field.mul(&x, &y) {
+            return None;
+        }
+        let z_inv = field.invert(&z);
+        let (x, y) = (field.mul(&x, &z_inv), field.mul(&y, &z_inv));
+        if !field.on_curve(&x, &y) {
+            return None;
+        }
+        Some(field.encode(&x, &y))
+    }
+
+    /// Ristretto encoding of a compressed edwards point, None if the bytes are not a point.
+    pub fn ristretto_encode_compressed(bytes: &[u8; 32]) -> Option<[u8; 32]> {
+        let field = Field::new();
+        let one = BigUint::from(1u32);
+        let y = field.decode(bytes);
+        let yy = field.mul(&y, &y);
+        let (was_square, x) = field.sqrt_ratio_m1(
+            &field.sub(&yy, &one),
+            &field.add(&field.mul(&field.d, &yy), &one),
+        );
+        if !was_square {
+            return None;
+        }
+        let x = if field.is_negative(&x) != (bytes[31] >> 7 == 1) {
+            field.neg(&x)
+        } else {
+            x
+        };
+        Some(field.encode(&x, &y))
+    }
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+
+    // current round of the devnet VRF account in the vrf module tests
+    const VRF_KEY: [u8; 32] = [
+        19, 165, 39, 119, 159, 19, 78, 162, 20, 63, 131, 53, 75, 122, 95, 50, 201, 69, 209, 134,
+        180, 72, 252, 22, 50, 120, 97, 161, 192, 247, 122, 248,
+    ];
+
+    const VRF_ALPHA: [u8; 32] = [
+        37, 235, 113, 38, 43, 20, 99, 243, 133, 27, 183, 229, 190, 115, 46, 163, 83, 254, 138, 59,
+        153, 32, 254, 5, 166, 108, 75, 91, 120, 203, 105, 202,
+    ];
+
+    const VRF_PROOF: [u8; 80] = [
+        246, 37, 145, 181, 50, 226, 68, 211, 69, 98, 249, 191, 216, 208, 250, 4, 26, 104, 145, 144,
+        30, 223, 219, 251, 186, 149, 100, 126, 142, 75, 8, 115, 255, 40, 245, 48, 19, 124, 203,
+        101, 130, 217, 221, 8, 116, 54, 35, 17, 43, 6, 29, 120, 91, 192, 45, 74, 198, 146, 2, 67,
+        8, 152, 53, 225, 210, 225, 49, 5, 77, 89, 243, 98, 86, 222, 248, 199, 48, 47, 205, 2,
+    ];
+
+    // ristretto encoding of VRF_KEY
+    const VRF_KEY_RISTRETTO: [u8; 32] = [
+        164, 16, 57, 56, 82, 50, 101, 173, 173, 69, 242, 158, 129, 209, 185, 43, 217, 103, 182,
+        194, 1, 59, 196, 150, 196, 204, 55, 116, 56, 229, 43, 122,
+    ];
+
+    #[test]
+    fn test_verify_proof() {
+        let proof = verify(&Pubkey::new_from_array(VRF_KEY), &VRF_ALPHA, &VRF_PROOF).unwrap();
+        assert_eq!(proof.gamma.compress().as_bytes()[..], VRF_PROOF[..32]);
+    }
+
+    #[test]
+    fn test_edwards_to_ristretto() {
+        let point = edwards_to_ristretto(&Pubkey::new_from_array(VRF_KEY)).unwrap();
+        assert_eq!(point.compress().to_bytes(), VRF_KEY_RISTRETTO);
+
+        // the ed25519 basepoint maps to the ristretto basepoint
+        let basepoint = curve25519_dalek::constants::ED25519_BASEPOINT_COMPRESSED.to_bytes();
+        let point = edwards_to_ristretto(&Pubkey::new_from_array(basepoint)).unwrap();
+        assert_eq!(point, RISTRETTO_BASEPOINT_POINT);
+
+        // random ed25519 keys match their ristretto counterparts
+        for seed in 1u64..8 {
+            let scalar = Scalar::from(seed.wrapping_mul(0x9e3779b97f4a7c15));
+            let edwards = &scalar * &curve25519_dalek::constants::ED25519_BASEPOINT_TABLE;
+            let point =
+                edwards_to_ristretto(&Pubkey::new_from_array(edwards.compress().to_bytes()));
+            assert_eq!(point.unwrap(), scalar * RISTRETTO_BASEPOINT_POINT);
+        }
+
+        // keys with a torsion component are rejected
+        let torsion = curve25519_dalek::constants::ED25519_BASEPOINT_POINT
+            + curve25519_dalek::constants::EIGHT_TORSION[1];
+        let key = Pubkey::new_from_array(torsion.compress().to_bytes());
+        assert!(edwards_to_ristretto(&key).is_none());
+    }
+
+    #[test]
+    fn test_reject_invalid_proof() {
+        let key = Pubkey::new_from_array(VRF_KEY);
+
+        let mut alpha = VRF_ALPHA;
+        alpha[0] ^= 1;
+        assert!(verify(&key, &alpha, &VRF_PROOF).is_err());
+
+        let mut proof = VRF_PROOF;
+        proof[60] ^= 1;
+        assert!(verify(&key, &VRF_ALPHA, &proof).is_err());
+
+        let mut other_key = VRF_KEY;
+        other_key[0] ^= 1;
+        assert!(verify(&Pubkey::new_from_array(other_key), &VRF_ALPHA, &VRF_PROOF).is_err());
+    }
+}
@@ -19,4 +19,74 @@ pub enum SwitchboardError {
     VrfCpiError,
     #[msg("Failed to send signed requestRandomness instruction")]
     VrfCpiSignedError,
+    #[msg("History buffer data is not a whole number of rows.")]
+    HistoryBufferMisaligned,
+    #[msg("OHLC interval must be positive and end after start.")]
+    InvalidOhlcConfig,
+    #[msg("Not enough history buffer rows for the requested query.")]
+    InsufficientHistoryError,
+    #[msg("History buffer account data is too small.")]
+    HistoryBufferTooSmall,
+    #[msg("History buffer insertion index is out of bounds.")]
+    HistoryBufferInvalidInsertionIdx,
+    #[msg("EMA half life must be greater than zero.")]
+    InvalidEmaHalfLife,
+    #[msg("Cannot compute a percent change from a zero value.")]
+    PercentChangeZeroBase,
+    #[msg("VRF proof failed verification.")]
+    InvalidVrfProof,
+    #[msg("VRF builder result does not match the round result.")]
+    VrfResultMismatch,
+    #[msg("Random range must have lo < hi.")]
+    InvalidRandomRange,
+    #[msg("Random probability must have a nonzero denominator no smaller than the numerator.")]
+    InvalidRandomProbability,
+    #[msg("Random weights must be non-empty with a nonzero total.")]
+    InvalidRandomWeights,
+    #[msg("VRF round was requested before the expected slot, timestamp or counter.")]
+    VrfStaleRoundError,
+    #[msg("VRF round has not been verified.")]
+    VrfNotVerifiedError,
+    #[msg("VRF callback has too many accounts or too much instruction data.")]
+    VrfCallbackTooLargeError,
+    #[msg("VRF callback does not target the expected program.")]
+    VrfCallbackProgramMismatch,
+    #[msg("VRF callback does not start with the expected instruction discriminator.")]
+    VrfCallbackDiscriminatorMismatch,
+    #[msg("VRF callback accounts do not match the expected accounts.")]
+    VrfCallbackAccountsMismatch,
+    #[msg("VRF callback was not invoked by the Switchboard program for this VRF account.")]
+    VrfCallbackUnauthorized,
+    #[msg("Failed to invoke Switchboard instruction")]
+    CpiError,
+    #[msg("Failed to invoke signed Switchboard instruction")]
+    CpiSignedError,
+    #[msg("VRF account already has a randomness request in progress.")]
+    VrfRequestInProgressError,
+    #[msg("Oracle queue, queue authority or data buffer do not match the VRF account's queue.")]
+    VrfQueueMismatch,
+    #[msg("Escrow is not the VRF account's escrow or is not owned by the program state.")]
+    VrfEscrowMismatch,
+    #[msg("Program state account does not match the expected PDA.")]
+    ProgramStateMismatch,
+    #[msg("Permission account does not match the expected PDA.")]
+    PermissionMismatch,
+    #[msg("Payer wallet cannot cover the VRF request cost.")]
+    VrfInsufficientFundsError,
+    #[msg("Oracle is not assigned to the aggregator's current round.")]
+    OracleMismatchError,
+    #[msg("Crank data buffer is smaller than the crank's row count.")]
+    CrankBufferTooSmall,
+    #[msg("No crank rows are ready to pop.")]
+    CrankEmptyError,
+    #[msg("Instruction discriminator does not match a known Switchboard instruction.")]
+    UnknownInstructionError,
+    #[msg("Instruction data could not be decoded.")]
+    InstructionDataError,
+    #[msg("Instruction has fewer accounts than it requires.")]
+    InstructionAccountsMissing,
+    #[msg("Lease job wallets do not match the wallet bumps, one wallet per job is required.")]
+    LeaseJobWalletsMismatch,
+    #[msg("Funder is not the authority's associated token account for the queue mint.")]
+    FunderMismatch,
 }
@@ -1,11 +1,17 @@
+use super::aggregator::AggregatorAccountData;
+use super::cadence::{analyze_update_cadence, UpdateCadenceReport};
 use super::decimal::SwitchboardDecimal;
 use super::error::SwitchboardError;
+use super::indicators::{percent_change, time_weighted_ema};
+use super::ohlc::{resample_ohlc, OhlcCandle, OhlcConfig};
 #[allow(unaligned_references)]
 use anchor_lang::prelude::*;
-use bytemuck::{try_cast_slice, try_from_bytes};
+use bytemuck::{cast_slice, try_cast_slice};
 use bytemuck::{Pod, Zeroable};
 use solana_program::account_info::AccountInfo;
 use std::cell::Ref;
+use std::iter::{Chain, Filter};
+use std::slice::Iter;
 use superslice::*;
 
 #[zero_copy]
@@ -18,6 +24,9 @@ pub struct AggregatorHistoryRow {
 unsafe impl Pod for AggregatorHistoryRow {}
 unsafe impl Zeroable for AggregatorHistoryRow {}
 
+// 8 byte discriminator followed by the u32 insertion index
+const HISTORY_BUFFER_HEADER_SIZE: usize = 12;
+
 pub struct AggregatorHistoryBuffer<'a> {
     pub insertion_idx: usize,
     pub rows: Ref<'a, [AggregatorHistoryRow]>,
@@ -27,26 +36,116 @@ impl<'a> AggregatorHistoryBuffer<'a> {
         history_buffer: &'a AccountInfo,
     ) -> anchor_lang::Result<AggregatorHistoryBuffer<'a>> {
         let data = history_buffer.try_borrow_data()?;
+        let (insertion_idx, _) = decode_history_rows(&data)?;
 
-        let mut disc_bytes = [0u8; 8];
-        disc_bytes.copy_from_slice(&data[..8]);
-        if disc_bytes != *b"BUFFERxx" {
-            return Err(SwitchboardError::AccountDiscriminatorMismatch.into());
-        }
-        let insertion_idx: u32 = try_from_bytes::<u32>(&data[8..12]).unwrap().clone();
-        let rows = Ref::map(data, |data| try_cast_slice(&data[12..]).unwrap());
-        return Ok(Self {
-            insertion_idx: insertion_idx as usize,
-            rows: rows,
-        });
+        // the row slice was validated above so the cast can not fail
+        let rows = Ref::map(data, |data| cast_slice(&data[HISTORY_BUFFER_HEADER_SIZE..]));
+        Ok(Self {
+            insertion_idx,
+            rows,
+        })
     }
 
     /// return the previous row in the history buffer for a given timestamp
     pub fn lower_bound(&self, timestamp: i64) -> Option<AggregatorHistoryRow> {
-        if self.rows[self.insertion_idx].timestamp == 0 {
-            return None;
+        AggregatorHistory::lower_bound(self, timestamp)
+    }
+}
+
+impl<'a> AggregatorHistory for AggregatorHistoryBuffer<'a> {
+    fn insertion_idx(&self) -> usize {
+        self.insertion_idx
+    }
+
+    fn rows(&self) -> &[AggregatorHistoryRow] {
+        &self.rows
+    }
+}
+
+/// An owned copy of a history buffer for off-chain use, e.g. account data fetched over RPC.
+#[derive(Clone, Default)]
+pub struct OwnedAggregatorHistoryBuffer {
+    pub insertion_idx: usize,
+    pub rows: Vec<AggregatorHistoryRow>,
+}
+impl OwnedAggregatorHistoryBuffer {
+    pub fn from_bytes(data: &[u8]) -> anchor_lang::Result<OwnedAggregatorHistoryBuffer> {
+        let (insertion_idx, rows) = decode_history_rows(data)?;
+        Ok(Self {
+            insertion_idx,
+            rows: rows.to_vec(),
+        })
+    }
+}
+
+impl AggregatorHistory for OwnedAggregatorHistoryBuffer {
+    fn insertion_idx(&self) -> usize {
+        self.insertion_idx
+    }
+
+    fn rows(&self) -> &[AggregatorHistoryRow] {
+        &self.rows
+    }
+}
+
+/// Queries shared by the account backed `AggregatorHistoryBuffer` and the
+/// `OwnedAggregatorHistoryBuffer`, so on-chain programs and off-chain indexers use the same code.
+pub trait AggregatorHistory {
+    /// index of the newest row in the circular buffer
+    fn insertion_idx(&self) -> usize;
+
+    fn rows(&self) -> &[AggregatorHistoryRow];
+
+    /// return the rows in chronological order, skipping rows that have not been written yet
+    fn iter(&self) -> HistoryRowIter<'_> {
+        chronological_rows(self.rows(), self.insertion_idx())
+    }
+
+    /// resample the history buffer into fixed-interval OHLC candles
+    fn ohlc(&self, config: &OhlcConfig) -> anchor_lang::Result<Vec<OhlcCandle>> {
+        resample_ohlc(self.iter(), config)
+    }
+
+    /// report gaps longer than `gap_threshold` seconds and the spacing between updates compared
+    /// against the aggregator's update configs
+    fn update_cadence(
+        &self,
+        aggregator: &AggregatorAccountData,
+        gap_threshold: i64,
+        now: i64,
+    ) -> anchor_lang::Result<UpdateCadenceReport> {
+        analyze_update_cadence(self.iter(), aggregator, gap_threshold, now)
+    }
+
+    /// time decayed exponential moving average of the value between `start_timestamp` and `now`,
+    /// see `indicators::time_weighted_ema`
+    fn ema(
+        &self,
+        half_life_seconds: u32,
+        start_timestamp: i64,
+        now: i64,
+    ) -> anchor_lang::Result<SwitchboardDecimal> {
+        time_weighted_ema(self, half_life_seconds, start_timestamp, now)
+    }
+
+    /// percent change between the value as of `now` and `lookback_seconds` earlier
+    fn percent_change(
+        &self,
+        now: i64,
+        lookback_seconds: i64,
+    ) -> anchor_lang::Result<SwitchboardDecimal> {
+        percent_change(self, now, lookback_seconds)
+    }
+
+    /// return the previous row in the history buffer for a given timestamp
+    fn lower_bound(&self, timestamp: i64) -> Option<AggregatorHistoryRow> {
+        match self.rows().get(self.insertion_idx()) {
+            Some(row) if row.timestamp != 0 => {}
+            _ => return None,
         }
-        let lower = &self.rows[..self.insertion_idx + 1];
+        let rows = self.rows();
+        let insertion_idx = self.insertion_idx();
+        let lower = &rows[..insertion_idx + 1];
         let lahr = lower.lower_bound_by(|x| {
             let other: i64 = x.timestamp;
             other.cmp(&timestamp)
@@ -58,10 +157,8 @@ impl<'a> AggregatorHistoryBuffer<'a> {
             return Some(lower[lahr - 1]);
         }
 
-        if self.insertion_idx + 1 < self.rows.len()
-            && self.rows[self.insertion_idx + 1].timestamp != 0
-        {
-            let upper = &self.rows[self.insertion_idx + 1..];
+        if insertion_idx + 1 < rows.len() && rows[insertion_idx + 1].timestamp != 0 {
+            let upper = &rows[insertion_idx + 1..];
             let uahr = upper.lower_bound_by(|x| {
                 let other: i64 = x.timestamp;
                 other.cmp(&timestamp)
@@ -77,6 +174,48 @@ impl<'a> AggregatorHistoryBuffer<'a> {
     }
 }
 
+/// decode the insertion index and rows from a raw history buffer snapshot, e.g. account data
+/// fetched over RPC. Rejects truncated data, partial rows and out of bounds insertion indexes.
+pub fn decode_history_rows(data: &[u8]) -> anchor_lang::Result<(usize, &[AggregatorHistoryRow])> {
+    if data.len() < HISTORY_BUFFER_HEADER_SIZE {
+        return Err(error!(SwitchboardError::HistoryBufferTooSmall));
+    }
+    if data[..8] != *b"BUFFERxx" {
+        return Err(error!(SwitchboardError::AccountDiscriminatorMismatch));
+    }
+    let mut idx_bytes = [0u8; 4];
+    idx_bytes.copy_from_slice(&data[8..HISTORY_BUFFER_HEADER_SIZE]);
+    let insertion_idx = u32::from_le_bytes(idx_bytes) as usize;
+    let rows: &[AggregatorHistoryRow] = try_cast_slice(&data[HISTORY_BUFFER_HEADER_SIZE..])
+        .map_err(|_| error!(SwitchboardError::HistoryBufferMisaligned))?;
+    // an empty buffer has nothing to index, all queries on it return nothing
+    if !rows.is_empty() && insertion_idx >= rows.len() {
+        return Err(error!(SwitchboardError::HistoryBufferInvalidInsertionIdx));
+    }
+    Ok((insertion_idx, rows))
+}
+
+/// Chronological iterator over the written rows of a history buffer.
+pub type HistoryRowIter<'b> = Filter<
+    Chain<Iter<'b, AggregatorHistoryRow>, Iter<'b, AggregatorHistoryRow>>,
+    fn(&&'b AggregatorHistoryRow) -> bool,
+>;
+
+/// iterate a circular history buffer from the oldest to the newest row. The newest row lives at
+/// `insertion_idx`, everything after it is older and rows with a zero timestamp are unused.
+pub fn chronological_rows(
+    rows: &[AggregatorHistoryRow],
+    insertion_idx: usize,
+) -> HistoryRowIter<'_> {
+    let (newer, older) = rows.split_at(rows.len().min(insertion_idx + 1));
+    older.iter().chain(newer.iter()).filter(is_written_row)
+}
+
+fn is_written_row(row: &&AggregatorHistoryRow) -> bool {
+    let timestamp: i64 = row.timestamp;
+    timestamp != 0
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -265,4 +404,137 @@ mod tests {
             Some(row) => panic!("retrieved row when no value was expected {:?}", row.value),
         };
     }
+
+    #[test]
+    fn test_history_buffer_iter() {
+        let mut history_data = HISTORY_BUFFER_DATA.clone();
+        let mut lamports = 0;
+        let history_account_info = AccountInfo::new(
+            &HISTORY_BUFFER_PUBKEY,
+            false,
+            false,
+            &mut lamports,
+            &mut history_data,
+            &SWITCHBOARD_V2_DEVNET,
+            false,
+            0,
+        );
+        let history_buffer = AggregatorHistoryBuffer::new(&history_account_info).unwrap();
+
+        let timestamps: Vec<i64> = history_buffer.iter().map(|row| row.timestamp).collect();
+        assert_eq!(timestamps.len(), 10);
+        assert_eq!(timestamps[0], 1646249713);
+        assert_eq!(timestamps[9], 1646249949);
+        assert!(timestamps.windows(2).all(|w| w[0] < w[1]));
+    }
+
+    fn history_buffer_data(insertion_idx: u32, num_rows: usize) -> Vec<u8> {
+        let mut data = b"BUFFERxx".to_vec();
+        data.extend_from_slice(&insertion_idx.to_le_bytes());
+        data.resize(
+            data.len() + num_rows * std::mem::size_of::<AggregatorHistoryRow>(),
+            0,
+        );
+        data
+    }
+
+    fn load_history_buffer(data: &mut [u8]) -> anchor_lang::Result<usize> {
+        let mut lamports = 0;
+        let history_account_info = AccountInfo::new(
+            &HISTORY_BUFFER_PUBKEY,
+            false,
+            false,
+            &mut lamports,
+            data,
+            &SWITCHBOARD_V2_DEVNET,
+            false,
+            0,
+        );
+        let history_buffer = AggregatorHistoryBuffer::new(&history_account_info)?;
+        assert!(history_buffer.lower_bound(1646249940).is_none());
+        Ok(history_buffer.rows.len())
+    }
+
+    #[test]
+    fn test_malformed_history_buffer() {
+        assert_eq!(
+            load_history_buffer(&mut []).unwrap_err(),
+            error!(SwitchboardError::HistoryBufferTooSmall)
+        );
+        assert_eq!(
+            load_history_buffer(&mut history_buffer_data(0, 1)[..10]).unwrap_err(),
+            error!(SwitchboardError::HistoryBufferTooSmall)
+        );
+
+        let mut data = history_buffer_data(0, 1);
+        data[0] = 0;
+        assert_eq!(
+            load_history_buffer(&mut data).unwrap_err(),
+            error!(SwitchboardError::AccountDiscriminatorMismatch)
+        );
+
+        let mut data = history_buffer_data(0, 2);
+        data.pop();
+        assert_eq!(
+            load_history_buffer(&mut data).unwrap_err(),
+            error!(SwitchboardError::HistoryBufferMisaligned)
+        );
+
+        assert_eq!(
+            load_history_buffer(&mut history_buffer_data(2, 2)).unwrap_err(),
+            error!(SwitchboardError::HistoryBufferInvalidInsertionIdx)
+        );
+    }
+
+    #[test]
+    fn test_empty_history_buffer() {
+        assert_eq!(
+            load_history_buffer(&mut history_buffer_data(0, 0)).unwrap(),
+            0
+        );
+        // unwritten rows are skipped
+        assert_eq!(
+            load_history_buffer(&mut history_buffer_data(1, 2)).unwrap(),
+            2
+        );
+        let data = history_buffer_data(5, 0);
+        let (_, rows) = decode_history_rows(&data).unwrap();
+        assert_eq!(chronological_rows(rows, 5).count(), 0);
+    }
+
+    #[test]
+    fn test_owned_history_buffer() {
+        let mut history_data = HISTORY_BUFFER_DATA.clone();
+        let mut lamports = 0;
+        let history_account_info = AccountInfo::new(
+            &HISTORY_BUFFER_PUBKEY,
+            false,
+            false,
+            &mut lamports,
+            &mut history_data,
+            &SWITCHBOARD_V2_DEVNET,
+            false,
+            0,
+        );
+        let history_buffer = AggregatorHistoryBuffer::new(&history_account_info).unwrap();
+        let owned_buffer = OwnedAggregatorHistoryBuffer::from_bytes(&HISTORY_BUFFER_DATA).unwrap();
+        assert_eq!(owned_buffer.insertion_idx, 1);
+        assert_eq!(owned_buffer.rows.len(), 10);
+
+        for timestamp in [
+            0, 1646249713, 1646249912, 1646249940, 1646249949, 2646249911,
+        ] {
+            let expected = history_buffer.lower_bound(timestamp).map(|row| row.value);
+            assert_eq!(
+                owned_buffer.lower_bound(timestamp).map(|row| row.value),
+                expected
+            );
+        }
+        assert!(history_buffer
+            .iter()
+            .map(|row| row.timestamp)
+            .eq(owned_buffer.iter().map(|row| row.timestamp)));
+
+        assert!(OwnedAggregatorHistoryBuffer::from_bytes(&HISTORY_BUFFER_DATA[..20]).is_err());
+    }
 }
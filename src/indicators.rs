@@ -0,0 +1,173 @@
+use super::decimal::SwitchboardDecimal;
+use super::error::SwitchboardError;
+use super::history_buffer::AggregatorHistory;
+use anchor_lang::prelude::*;
+use rust_decimal::prelude::FromPrimitive;
+use rust_decimal::Decimal;
+use std::convert::TryInto;
+
+/// Exponentially weighted moving average of the feed value between `start_timestamp` and `now`.
+///
+/// Each value is held until the next update, so a value contributes in proportion to how long it
+/// was the latest result rather than once per row, and its weight halves every
+/// `half_life_seconds`. The average is seeded with the value as of `start_timestamp`, or the
+/// first update after it if the buffer does not reach back that far.
+pub fn time_weighted_ema<H: AggregatorHistory + ?Sized>(
+    history: &H,
+    half_life_seconds: u32,
+    start_timestamp: i64,
+    now: i64,
+) -> anchor_lang::Result<SwitchboardDecimal> {
+    if half_life_seconds == 0 {
+        return Err(error!(SwitchboardError::InvalidEmaHalfLife));
+    }
+
+    let mut state: Option<(i64, Decimal, Decimal)> = history
+        .lower_bound(start_timestamp)
+        .map(|row| -> anchor_lang::Result<(i64, Decimal, Decimal)> {
+            let value: Decimal = row.value.try_into()?;
+            Ok((start_timestamp, value, value))
+        })
+        .transpose()?;
+    for row in history.iter() {
+        let timestamp: i64 = row.timestamp;
+        if timestamp <= start_timestamp || timestamp > now {
+            continue;
+        }
+        let value: Decimal = row.value.try_into()?;
+        state = Some(match state {
+            None => (timestamp, value, value),
+            Some((last_timestamp, ema, last_value)) => (
+                timestamp,
+                decay(
+                    ema,
+                    last_value,
+                    timestamp - last_timestamp,
+                    half_life_seconds,
+                )?,
+                value,
+            ),
+        });
+    }
+
+    let (last_timestamp, ema, last_value) =
+        state.ok_or(error!(SwitchboardError::InsufficientHistoryError))?;
+    let ema = decay(ema, last_value, now - last_timestamp, half_life_seconds)?;
+    Ok(SwitchboardDecimal::from_rust_decimal(ema))
+}
+
+/// Percent change between the value as of `now` and the value as of `lookback_seconds` earlier,
+/// e.g. `5` for a 5% increase.
+pub fn percent_change<H: AggregatorHistory + ?Sized>(
+    history: &H,
+    now: i64,
+    lookback_seconds: i64,
+) -> anchor_lang::Result<SwitchboardDecimal> {
+    let past_timestamp = now
+        .checked_sub(lookback_seconds)
+        .ok_or(error!(SwitchboardError::IntegerOverflowError))?;
+    let current: Decimal = history
+        .lower_bound(now)
+        .ok_or(error!(SwitchboardError::InsufficientHistoryError))?
+        .value
+        .try_into()?;
+    let past: Decimal = history
+        .lower_bound(past_timestamp)
+        .ok_or(error!(SwitchboardError::InsufficientHistoryError))?
+        .value
+        .try_into()?;
+    if past.is_zero() {
+        return Err(error!(SwitchboardError::PercentChangeZeroBase));
+    }
+
+    let change = (current - past)
+        .checked_div(past)
+        .and_then(|ratio| ratio.checked_mul(Decimal::new(100, 0)))
+        .ok_or(error!(SwitchboardError::IntegerOverflowError))?;
+    Ok(SwitchboardDecimal::from_rust_decimal(change))
+}
+
+// move the average towards the value it held for the last `elapsed` seconds
+fn decay(
+    ema: Decimal,
+    held_value: Decimal,
+    elapsed: i64,
+    half_life_seconds: u32,
+) -> anchor_lang::Result<Decimal> {
+    if elapsed <= 0 {
+        return Ok(ema);
+    }
+    let retained = Decimal::from_f64(0.5f64.powf(elapsed as f64 / half_life_seconds as f64))
+        .ok_or(error!(SwitchboardError::DecimalConversionError))?;
+    ema.checked_mul(retained)
+        .and_then(|ema| {
+            held_value
+                .checked_mul(Decimal::new(1, 0) - retained)
+                .and_then(|held| ema.checked_add(held))
+        })
+        .ok_or(error!(SwitchboardError::IntegerOverflowError))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::history_buffer::{AggregatorHistoryRow, OwnedAggregatorHistoryBuffer};
+
+    fn history(rows: &[(i64, f64)]) -> OwnedAggregatorHistoryBuffer {
+        OwnedAggregatorHistoryBuffer {
+            insertion_idx: rows.len() - 1,
+            rows: rows
+                .iter()
+                .map(|(timestamp, value)| AggregatorHistoryRow {
+                    timestamp: *timestamp,
+                    value: SwitchboardDecimal::from_f64(*value),
+                })
+                .collect(),
+        }
+    }
+
+    fn to_f64(value: SwitchboardDecimal) -> f64 {
+        value.try_into().unwrap()
+    }
+
+    #[test]
+    fn test_ema_weights_by_time() {
+        let history = history(&[(100, 10.0), (110, 20.0)]);
+        // the new value has not been held for any time yet
+        assert_eq!(to_f64(history.ema(10, 100, 110).unwrap()), 10.0);
+        assert_eq!(to_f64(history.ema(10, 100, 120).unwrap()), 15.0);
+        assert_eq!(to_f64(history.ema(10, 100, 130).unwrap()), 17.5);
+        // seeded with the value as of the start timestamp
+        assert_eq!(to_f64(history.ema(10, 115, 125).unwrap()), 20.0);
+    }
+
+    #[test]
+    fn test_ema_irregular_sampling() {
+        // many updates at the same value should not outweigh a long held value
+        let sparse = history(&[(100, 10.0), (200, 20.0)]);
+        let dense = history(&[(100, 10.0), (190, 10.0), (195, 10.0), (200, 20.0)]);
+        assert_eq!(
+            to_f64(sparse.ema(30, 100, 230).unwrap()),
+            to_f64(dense.ema(30, 100, 230).unwrap())
+        );
+    }
+
+    #[test]
+    fn test_ema_errors() {
+        let history = history(&[(100, 10.0), (110, 20.0)]);
+        assert!(history.ema(0, 100, 120).is_err());
+        assert!(history.ema(10, 50, 90).is_err());
+    }
+
+    #[test]
+    fn test_percent_change() {
+        let history = history(&[(100, 10.0), (110, 12.5), (120, 5.0)]);
+        assert_eq!(to_f64(history.percent_change(115, 10).unwrap()), 25.0);
+        assert_eq!(to_f64(history.percent_change(120, 10).unwrap()), -60.0);
+        assert_eq!(to_f64(history.percent_change(110, 0).unwrap()), 0.0);
+        assert!(history.percent_change(110, 20).is_err());
+
+        let zero = self::history(&[(100, 0.0), (110, 1.0)]);
+        assert!(zero.percent_change(110, 10).is_err());
+    }
+}
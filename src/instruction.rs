@@ -0,0 +1,147 @@
+//! Instruction encoding and invocation shared by the CPI account structs and the client builders.
+use super::error::SwitchboardError;
+use anchor_lang::prelude::*;
+use solana_program::instruction::Instruction;
+use solana_program::program::{invoke, invoke_signed};
+use solana_program::pubkey::Pubkey;
+
+// Instructions with no accounts struct in this crate, which the client builds and the decoder
+// reads.
+pub(crate) const AGGREGATOR_INIT_DISCRIMINATOR: [u8; 8] = [200, 41, 88, 11, 36, 21, 181, 110];
+pub(crate) const AGGREGATOR_SAVE_RESULT_DISCRIMINATOR: [u8; 8] = [21, 67, 5, 0, 74, 168, 51, 192];
+pub(crate) const JOB_INIT_DISCRIMINATOR: [u8; 8] = [101, 86, 105, 192, 34, 201, 147, 159];
+pub(crate) const CRANK_PUSH_DISCRIMINATOR: [u8; 8] = [155, 175, 160, 18, 7, 147, 249, 16];
+pub(crate) const CRANK_POP_DISCRIMINATOR: [u8; 8] = [66, 57, 216, 251, 165, 107, 128, 98];
+pub(crate) const ORACLE_HEARTBEAT_DISCRIMINATOR: [u8; 8] = [10, 175, 217, 130, 111, 35, 117, 54];
+
+/// Build a Switchboard instruction whose data is the instruction discriminator followed by the
+/// borsh serialized params.
+pub(crate) fn build_instruction<T: AnchorSerialize>(
+    program_id: Pubkey,
+    discriminator: [u8; 8],
+    params: T,
+    accounts: Vec<AccountMeta>,
+) -> anchor_lang::Result<Instruction> {
+    let mut data: Vec<u8> = discriminator.to_vec();
+    let mut param_vec: Vec<u8> = params.try_to_vec()?;
+    data.append(&mut param_vec);
+
+    Ok(Instruction::new_with_bytes(program_id, &data, accounts))
+}
+
+/// Invoke an instruction, signing with `signer_seeds` when given. Failures map to `CpiError` or
+/// `CpiSignedError`.
+pub(crate) fn invoke_instruction(
+    instruction: &Instruction,
+    account_infos: &[AccountInfo],
+    signer_seeds: Option<&[&[&[u8]]]>,
+) -> anchor_lang::Result<()> {
+    match signer_seeds {
+        Some(signer_seeds) => invoke_signed(instruction, account_infos, signer_seeds)
+            .map_err(|_| error!(SwitchboardError::CpiSignedError)),
+        None => invoke(instruction, account_infos).map_err(|_| error!(SwitchboardError::CpiError)),
+    }
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+    use anchor_spl::token::spl_token::state::{Account as SplAccount, AccountState};
+    use solana_program::program_pack::Pack;
+
+    /// Backing storage for the account infos passed to the CPI structs in instruction tests.
+    pub(crate) struct TestAccounts {
+        pub keys: Vec<Pubkey>,
+        lamports: Vec<u64>,
+        data: Vec<Vec<u8>>,
+        owners: Vec<Pubkey>,
+    }
+
+    impl TestAccounts {
+        /// `len` accounts with unique keys, where `token_accounts` are initialized token accounts
+        /// owned by the account at `token_owner`.
+        pub fn new(len: usize, token_accounts: &[usize], token_owner: usize) -> TestAccounts {
+            let keys: Vec<Pubkey> = (0..len).map(|_| Pubkey::new_unique()).collect();
+            let mut data = vec![vec![]; len];
+            let mut owners = keys.clone();
+            for idx in token_accounts.iter() {
+                data[*idx] = vec![0u8; SplAccount::LEN];
+                SplAccount {
+                    owner: keys[token_owner],
+                    state: AccountState::Initialized,
+                    ..Default::default()
+                }
+                .pack_into_slice(&mut data[*idx]);
+                owners[*idx] = anchor_spl::token::ID;
+            }
+            TestAccounts {
+                keys,
+                lamports: vec![0; len],
+                data,
+                owners,
+            }
+        }
+
+        /// Non signer account infos, writable unless listed in `readonly`.
+        pub fn infos(&mut self, readonly: &[usize]) -> Vec<AccountInfo<'_>> {
+            self.keys
+                .iter()
+                .zip(self.lamports.iter_mut())
+                .zip(self.data.iter_mut())
+                .zip(self.owners.iter())
+                .enumerate()
+                .map(|(idx, (((key, lamports), data), owner))| {
+                    let is_writable = !readonly.contains(&idx);
+                    AccountInfo::new(key, false, is_writable, lamports, data, owner, false, 0)
+                })
+                .collect()
+        }
+    }
+
+    #[derive(AnchorSerialize, AnchorDeserialize)]
+    struct Params {
+        bump: u8,
+        amount: u64,
+    }
+
+    #[test]
+    fn test_discriminators() {
+        let discriminators = [
+            (AGGREGATOR_INIT_DISCRIMINATOR, "aggregator_init"),
+            (
+                AGGREGATOR_SAVE_RESULT_DISCRIMINATOR,
+                "aggregator_save_result",
+            ),
+            (JOB_INIT_DISCRIMINATOR, "job_init"),
+            (CRANK_PUSH_DISCRIMINATOR, "crank_push"),
+            (CRANK_POP_DISCRIMINATOR, "crank_pop"),
+            (ORACLE_HEARTBEAT_DISCRIMINATOR, "oracle_heartbeat"),
+        ];
+        for (discriminator, name) in discriminators.iter() {
+            let hash = solana_program::hash::hash(format!("global:{}", name).as_bytes());
+            assert_eq!(discriminator[..], hash.to_bytes()[..8]);
+        }
+    }
+
+    #[test]
+    fn test_build_instruction() {
+        let program_id = Pubkey::new_unique();
+        let accounts = vec![AccountMeta::new(Pubkey::new_unique(), true)];
+        let ix = build_instruction(
+            program_id,
+            [1, 2, 3, 4, 5, 6, 7, 8],
+            Params {
+                bump: 9,
+                amount: 10,
+            },
+            accounts.clone(),
+        )
+        .unwrap();
+        assert_eq!(ix.program_id, program_id);
+        assert_eq!(ix.accounts, accounts);
+        assert_eq!(
+            ix.data,
+            [1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 0, 0, 0, 0, 0, 0, 0]
+        );
+    }
+}
@@ -0,0 +1,754 @@
+//! Lease instructions. A lease escrows the tokens that pay oracles to update an aggregator on a
+//! queue.
+use super::error::SwitchboardError;
+use super::instruction::{build_instruction, invoke_instruction};
+use anchor_lang::prelude::*;
+use anchor_spl::token::TokenAccount;
+use solana_program::instruction::Instruction;
+use solana_program::pubkey::Pubkey;
+
+/// Associated token account program id. Prints out as "ATokenGPvbdGVxr1b2hvZbsiqW5xWH25efTNsLJA8knL"
+pub const ASSOCIATED_TOKEN_PROGRAM_ID: Pubkey = Pubkey::new_from_array([
+    140, 151, 37, 143, 78, 36, 137, 241, 187, 61, 16, 41, 20, 142, 13, 131, 11, 90, 19, 153, 218,
+    255, 16, 132, 4, 142, 123, 216, 219, 233, 248, 89,
+]);
+
+/// A lease's escrow, the lease's associated token account for `mint`.
+pub fn escrow_address(lease: &Pubkey, mint: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(
+        &[
+            lease.as_ref(),
+            anchor_spl::token::ID.as_ref(),
+            mint.as_ref(),
+        ],
+        &ASSOCIATED_TOKEN_PROGRAM_ID,
+    )
+    .0
+}
+
+/// Fund a new lease for `aggregator` on `queue` from the owner's `funder` wallet.
+#[derive(Accounts)]
+#[instruction(params: LeaseInitParams)] // rpc parameters hint
+pub struct LeaseInit<'info> {
+    #[account(
+        mut,
+        seeds = [
+            b"LeaseAccountData",
+            queue.key().as_ref(),
+            aggregator.key().as_ref()
+        ],
+        bump = params.lease_bump
+    )]
+    pub lease: AccountInfo<'info>,
+    #[account(mut)]
+    pub queue: AccountInfo<'info>,
+    pub aggregator: AccountInfo<'info>,
+    #[account(mut, constraint = funder.owner == owner.key())]
+    pub funder: Account<'info, TokenAccount>,
+    #[account(mut, signer)]
+    pub payer: AccountInfo<'info>,
+    pub system_program: AccountInfo<'info>,
+    pub token_program: AccountInfo<'info>,
+    #[account(signer)]
+    pub owner: AccountInfo<'info>,
+    #[account(
+        mut,
+        constraint = escrow.owner == lease.key(),
+        address = escrow_address(&lease.key(), &mint.key())
+    )]
+    pub escrow: Account<'info, TokenAccount>,
+    #[account(seeds = [b"STATE"], bump = params.state_bump)]
+    pub program_state: AccountInfo<'info>,
+    pub mint: AccountInfo<'info>,
+}
+
+#[derive(Clone, Debug, AnchorSerialize, AnchorDeserialize)]
+pub struct LeaseInitParams {
+    pub load_amount: u64,
+    pub withdraw_authority: Pubkey,
+    pub lease_bump: u8,
+    pub state_bump: u8,
+    pub wallet_bumps: Vec<u8>,
+}
+
+impl<'info> LeaseInit<'info> {
+    pub(crate) fn discriminator() -> [u8; 8] {
+        [168, 190, 157, 252, 159, 226, 241, 89]
+    }
+
+    /// `job_wallets` are the token wallets of the aggregator's job authors, in job order, whose
+    /// bumps are `params.wallet_bumps`. They are appended as writable accounts.
+    pub fn get_instruction(
+        &self,
+        program_id: Pubkey,
+        params: LeaseInitParams,
+        job_wallets: &[AccountInfo],
+    ) -> anchor_lang::Result<Instruction> {
+        if job_wallets.len() != params.wallet_bumps.len() {
+            return Err(error!(SwitchboardError::LeaseJobWalletsMismatch));
+        }
+        let mut accounts = self.to_account_metas(None);
+        accounts.extend(
+            job_wallets
+                .iter()
+                .map(|wallet| AccountMeta::new(*wallet.key, false)),
+        );
+        build_instruction(program_id, LeaseInit::discriminator(), params, accounts)
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    pub fn invoke(
+        &self,
+        program: AccountInfo<'info>,
+        load_amount: u64,
+        withdraw_authority: Pubkey,
+        lease_bump: u8,
+        state_bump: u8,
+        wallet_bumps: Vec<u8>,
+        job_wallets: &[AccountInfo<'info>],
+    ) -> anchor_lang::Result<()> {
+        let cpi_params = LeaseInitParams {
+            load_amount,
+            withdraw_authority,
+            lease_bump,
+            state_bump,
+            wallet_bumps,
+        };
+        let instruction = self.get_instruction(*program.key, cpi_params, job_wallets)?;
+        let mut account_infos = self.to_account_infos();
+        account_infos.extend_from_slice(job_wallets);
+
+        invoke_instruction(&instruction, &account_infos, None)
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    pub fn invoke_signed(
+        &self,
+        program: AccountInfo<'info>,
+        load_amount: u64,
+        withdraw_authority: Pubkey,
+        lease_bump: u8,
+        state_bump: u8,
+        wallet_bumps: Vec<u8>,
+        job_wallets: &[AccountInfo<'info>],
+        signer_seeds: &[&[&[u8]]],
+    ) -> anchor_lang::Result<()> {
+        let cpi_params = LeaseInitParams {
+            load_amount,
+            withdraw_authority,
+            lease_bump,
+            state_bump,
+            wallet_bumps,
+        };
+        let instruction = self.get_instruction(*program.key, cpi_params, job_wallets)?;
+        let mut account_infos = self.to_account_infos();
+        account_infos.extend_from_slice(job_wallets);
+
+        invoke_instruction(&instruction, &account_infos, Some(signer_seeds))
+    }
+
+    fn to_account_infos(&self) -> Vec<AccountInfo<'info>> {
+        vec![
+            self.lease.clone(),
+            self.queue.clone(),
+            self.aggregator.clone(),
+            self.funder.to_account_info(),
+            self.payer.clone(),
+            self.system_program.clone(),
+            self.token_program.clone(),
+            self.owner.clone(),
+            self.escrow.to_account_info(),
+            self.program_state.clone(),
+            self.mint.clone(),
+        ]
+    }
+
+    #[allow(unused_variables)]
+    fn to_account_metas(&self, is_signer: Option<bool>) -> Vec<AccountMeta> {
+        vec![
+            AccountMeta {
+                pubkey: *self.lease.key,
+                is_signer: self.lease.is_signer,
+                is_writable: self.lease.is_writable,
+            },
+            AccountMeta {
+                pubkey: *self.queue.key,
+                is_signer: self.queue.is_signer,
+                is_writable: self.queue.is_writable,
+            },
+            AccountMeta {
+                pubkey: *self.aggregator.key,
+                is_signer: self.aggregator.is_signer,
+                is_writable: self.aggregator.is_writable,
+            },
+            AccountMeta {
+                pubkey: self.funder.key(),
+                is_signer: self.funder.to_account_info().is_signer,
+                is_writable: self.funder.to_account_info().is_writable,
+            },
+            AccountMeta {
+                pubkey: *self.payer.key,
+                is_signer: true, // overwrite, has to sign
+                is_writable: self.payer.is_writable,
+            },
+            AccountMeta {
+                pubkey: *self.system_program.key,
+                is_signer: self.system_program.is_signer,
+                is_writable: self.system_program.is_writable,
+            },
+            AccountMeta {
+                pubkey: *self.token_program.key,
+                is_signer: self.token_program.is_signer,
+                is_writable: self.token_program.is_writable,
+            },
+            AccountMeta {
+                pubkey: *self.owner.key,
+                is_signer: true, // overwrite, has to sign
+                is_writable: self.owner.is_writable,
+            },
+            AccountMeta {
+                pubkey: self.escrow.key(),
+                is_signer: self.escrow.to_account_info().is_signer,
+                is_writable: self.escrow.to_account_info().is_writable,
+            },
+            AccountMeta {
+                pubkey: *self.program_state.key,
+                is_signer: self.program_state.is_signer,
+                is_writable: self.program_state.is_writable,
+            },
+            AccountMeta {
+                pubkey: *self.mint.key,
+                is_signer: self.mint.is_signer,
+                is_writable: self.mint.is_writable,
+            },
+        ]
+    }
+}
+
+/// Top up an existing lease's escrow from the owner's `funder` wallet.
+#[derive(Accounts)]
+#[instruction(params: LeaseExtendParams)] // rpc parameters hint
+pub struct LeaseExtend<'info> {
+    #[account(
+        mut,
+        seeds = [
+            b"LeaseAccountData",
+            queue.key().as_ref(),
+            aggregator.key().as_ref()
+        ],
+        bump = params.lease_bump
+    )]
+    pub lease: AccountInfo<'info>,
+    pub aggregator: AccountInfo<'info>,
+    pub queue: AccountInfo<'info>,
+    #[account(mut, constraint = funder.owner == owner.key())]
+    pub funder: Account<'info, TokenAccount>,
+    #[account(signer)]
+    pub owner: AccountInfo<'info>,
+    #[account(
+        mut,
+        constraint = escrow.owner == lease.key(),
+        address = escrow_address(&lease.key(), &mint.key())
+    )]
+    pub escrow: Account<'info, TokenAccount>,
+    pub token_program: AccountInfo<'info>,
+    #[account(seeds = [b"STATE"], bump = params.state_bump)]
+    pub program_state: AccountInfo<'info>,
+    pub mint: AccountInfo<'info>,
+}
+
+#[derive(Clone, Debug, AnchorSerialize, AnchorDeserialize)]
+pub struct LeaseExtendParams {
+    pub load_amount: u64,
+    pub lease_bump: u8,
+    pub state_bump: u8,
+    pub wallet_bumps: Vec<u8>,
+}
+
+impl<'info> LeaseExtend<'info> {
+    pub(crate) fn discriminator() -> [u8; 8] {
+        [202, 70, 141, 29, 136, 142, 230, 118]
+    }
+
+    /// `job_wallets` are the token wallets of the aggregator's job authors, in job order, whose
+    /// bumps are `params.wallet_bumps`. They are appended as writable accounts.
+    pub fn get_instruction(
+        &self,
+        program_id: Pubkey,
+        params: LeaseExtendParams,
+        job_wallets: &[AccountInfo],
+    ) -> anchor_lang::Result<Instruction> {
+        if job_wallets.len() != params.wallet_bumps.len() {
+            return Err(error!(SwitchboardError::LeaseJobWalletsMismatch));
+        }
+        let mut accounts = self.to_account_metas(None);
+        accounts.extend(
+            job_wallets
+                .iter()
+                .map(|wallet| AccountMeta::new(*wallet.key, false)),
+        );
+        build_instruction(program_id, LeaseExtend::discriminator(), params, accounts)
+    }
+
+    pub fn invoke(
+        &self,
+        program: AccountInfo<'info>,
+        load_amount: u64,
+        lease_bump: u8,
+        state_bump: u8,
+        wallet_bumps: Vec<u8>,
+        job_wallets: &[AccountInfo<'info>],
+    ) -> anchor_lang::Result<()> {
+        let cpi_params = LeaseExtendParams {
+            load_amount,
+            lease_bump,
+            state_bump,
+            wallet_bumps,
+        };
+        let instruction = self.get_instruction(*program.key, cpi_params, job_wallets)?;
+        let mut account_infos = self.to_account_infos();
+        account_infos.extend_from_slice(job_wallets);
+
+        invoke_instruction(&instruction, &account_infos, None)
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    pub fn invoke_signed(
+        &self,
+        program: AccountInfo<'info>,
+        load_amount: u64,
+        lease_bump: u8,
+        state_bump: u8,
+        wallet_bumps: Vec<u8>,
+        job_wallets: &[AccountInfo<'info>],
+        signer_seeds: &[&[&[u8]]],
+    ) -> anchor_lang::Result<()> {
+        let cpi_params = LeaseExtendParams {
+            load_amount,
+            lease_bump,
+            state_bump,
+            wallet_bumps,
+        };
+        let instruction = self.get_instruction(*program.key, cpi_params, job_wallets)?;
+        let mut account_infos = self.to_account_infos();
+        account_infos.extend_from_slice(job_wallets);
+
+        invoke_instruction(&instruction, &account_infos, Some(signer_seeds))
+    }
+
+    fn to_account_infos(&self) -> Vec<AccountInfo<'info>> {
+        vec![
+            self.lease.clone(),
+            self.aggregator.clone(),
+            self.queue.clone(),
+            self.funder.to_account_info(),
+            self.owner.clone(),
+            self.escrow.to_account_info(),
+            self.token_program.clone(),
+            self.program_state.clone(),
+            self.mint.clone(),
+        ]
+    }
+
+    #[allow(unused_variables)]
+    fn to_account_metas(&self, is_signer: Option<bool>) -> Vec<AccountMeta> {
+        vec![
+            AccountMeta {
+                pubkey: *self.lease.key,
+                is_signer: self.lease.is_signer,
+                is_writable: self.lease.is_writable,
+            },
+            AccountMeta {
+                pubkey: *self.aggregator.key,
+                is_signer: self.aggregator.is_signer,
+                is_writable: self.aggregator.is_writable,
+            },
+            AccountMeta {
+                pubkey: *self.queue.key,
+                is_signer: self.queue.is_signer,
+                is_writable: self.queue.is_writable,
+            },
+            AccountMeta {
+                pubkey: self.funder.key(),
+                is_signer: self.funder.to_account_info().is_signer,
+                is_writable: self.funder.to_account_info().is_writable,
+            },
+            AccountMeta {
+                pubkey: *self.owner.key,
+                is_signer: true, // overwrite, has to sign
+                is_writable: self.owner.is_writable,
+            },
+            AccountMeta {
+                pubkey: self.escrow.key(),
+                is_signer: self.escrow.to_account_info().is_signer,
+                is_writable: self.escrow.to_account_info().is_writable,
+            },
+            AccountMeta {
+                pubkey: *self.token_program.key,
+                is_signer: self.token_program.is_signer,
+                is_writable: self.token_program.is_writable,
+            },
+            AccountMeta {
+                pubkey: *self.program_state.key,
+                is_signer: self.program_state.is_signer,
+                is_writable: self.program_state.is_writable,
+            },
+            AccountMeta {
+                pubkey: *self.mint.key,
+                is_signer: self.mint.is_signer,
+                is_writable: self.mint.is_writable,
+            },
+        ]
+    }
+}
+
+/// Withdraw `amount` from a lease's escrow to `withdraw_account`.
+#[derive(Accounts)]
+#[instruction(params: LeaseWithdrawParams)] // rpc parameters hint
+pub struct LeaseWithdraw<'info> {
+    #[account(
+        mut,
+        seeds = [
+            b"LeaseAccountData",
+            queue.key().as_ref(),
+            aggregator.key().as_ref()
+        ],
+        bump = params.lease_bump
+    )]
+    pub lease: AccountInfo<'info>,
+    #[account(
+        mut,
+        constraint = escrow.owner == lease.key(),
+        address = escrow_address(&lease.key(), &mint.key())
+    )]
+    pub escrow: Account<'info, TokenAccount>,
+    pub aggregator: AccountInfo<'info>,
+    pub queue: AccountInfo<'info>,
+    #[account(signer)]
+    pub withdraw_authority: AccountInfo<'info>,
+    #[account(mut)]
+    pub withdraw_account: Account<'info, TokenAccount>,
+    pub token_program: AccountInfo<'info>,
+    #[account(seeds = [b"STATE"], bump = params.state_bump)]
+    pub program_state: AccountInfo<'info>,
+    pub mint: AccountInfo<'info>,
+}
+
+#[derive(Clone, Debug, AnchorSerialize, AnchorDeserialize)]
+pub struct LeaseWithdrawParams {
+    pub state_bump: u8,
+    pub lease_bump: u8,
+    pub amount: u64,
+}
+
+impl<'info> LeaseWithdraw<'info> {
+    pub(crate) fn discriminator() -> [u8; 8] {
+        [186, 41, 100, 248, 234, 81, 61, 169]
+    }
+
+    pub fn get_instruction(
+        &self,
+        program_id: Pubkey,
+        params: LeaseWithdrawParams,
+    ) -> anchor_lang::Result<Instruction> {
+        build_instruction(
+            program_id,
+            LeaseWithdraw::discriminator(),
+            params,
+            self.to_account_metas(None),
+        )
+    }
+
+    pub fn invoke(
+        &self,
+        program: AccountInfo<'info>,
+        state_bump: u8,
+        lease_bump: u8,
+        amount: u64,
+    ) -> anchor_lang::Result<()> {
+        let cpi_params = LeaseWithdrawParams {
+            state_bump,
+            lease_bump,
+            amount,
+        };
+        let instruction = self.get_instruction(*program.key, cpi_params)?;
+        let account_infos = self.to_account_infos();
+
+        invoke_instruction(&instruction, &account_infos, None)
+    }
+
+    pub fn invoke_signed(
+        &self,
+        program: AccountInfo<'info>,
+        state_bump: u8,
+        lease_bump: u8,
+        amount: u64,
+        signer_seeds: &[&[&[u8]]],
+    ) -> anchor_lang::Result<()> {
+        let cpi_params = LeaseWithdrawParams {
+            state_bump,
+            lease_bump,
+            amount,
+        };
+        let instruction = self.get_instruction(*program.key, cpi_params)?;
+        let account_infos = self.to_account_infos();
+
+        invoke_instruction(&instruction, &account_infos, Some(signer_seeds))
+    }
+
+    fn to_account_infos(&self) -> Vec<AccountInfo<'info>> {
+        vec![
+            self.lease.clone(),
+            self.escrow.to_account_info(),
+            self.aggregator.clone(),
+            self.queue.clone(),
+            self.withdraw_authority.clone(),
+            self.withdraw_account.to_account_info(),
+            self.token_program.clone(),
+            self.program_state.clone(),
+            self.mint.clone(),
+        ]
+    }
+
+    #[allow(unused_variables)]
+    fn to_account_metas(&self, is_signer: Option<bool>) -> Vec<AccountMeta> {
+        vec![
+            AccountMeta {
+                pubkey: *self.lease.key,
+                is_signer: self.lease.is_signer,
+                is_writable: self.lease.is_writable,
+            },
+            AccountMeta {
+                pubkey: self.escrow.key(),
+                is_signer: self.escrow.to_account_info().is_signer,
+                is_writable: self.escrow.to_account_info().is_writable,
+            },
+            AccountMeta {
+                pubkey: *self.aggregator.key,
+                is_signer: self.aggregator.is_signer,
+                is_writable: self.aggregator.is_writable,
+            },
+            AccountMeta {
+                pubkey: *self.queue.key,
+                is_signer: self.queue.is_signer,
+                is_writable: self.queue.is_writable,
+            },
+            AccountMeta {
+                pubkey: *self.withdraw_authority.key,
+                is_signer: true, // overwrite, has to sign
+                is_writable: self.withdraw_authority.is_writable,
+            },
+            AccountMeta {
+                pubkey: self.withdraw_account.key(),
+                is_signer: self.withdraw_account.to_account_info().is_signer,
+                is_writable: self.withdraw_account.to_account_info().is_writable,
+            },
+            AccountMeta {
+                pubkey: *self.token_program.key,
+                is_signer: self.token_program.is_signer,
+                is_writable: self.token_program.is_writable,
+            },
+            AccountMeta {
+                pubkey: *self.program_state.key,
+                is_signer: self.program_state.is_signer,
+                is_writable: self.program_state.is_writable,
+            },
+            AccountMeta {
+                pubkey: *self.mint.key,
+                is_signer: self.mint.is_signer,
+                is_writable: self.mint.is_writable,
+            },
+        ]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::instruction::tests::TestAccounts;
+
+    // spl-associated-token-account's address for [1; 32] and the native mint
+    const ESCROW: [u8; 32] = [
+        99, 170, 225, 226, 68, 228, 78, 136, 103, 32, 242, 111, 231, 55, 49, 57, 51, 186, 6, 213,
+        10, 254, 9, 7, 181, 161, 51, 33, 53, 131, 50, 39,
+    ];
+
+    #[test]
+    fn test_lease_escrow_address() {
+        let lease = Pubkey::new_from_array([1; 32]);
+        let mint = anchor_spl::token::spl_token::native_mint::ID;
+        let escrow = escrow_address(&lease, &mint);
+        assert_eq!(escrow, Pubkey::new_from_array(ESCROW));
+        assert!(!escrow.is_on_curve());
+        assert_ne!(escrow, escrow_address(&lease, &Pubkey::new_unique()));
+        assert_ne!(escrow, escrow_address(&Pubkey::new_unique(), &mint));
+    }
+
+    #[test]
+    fn test_lease_init_instruction() {
+        // funder and escrow are token accounts, followed by the wallets of two jobs
+        let mut test_accounts = TestAccounts::new(13, &[3, 8, 11, 12], 7);
+        let keys = test_accounts.keys.clone();
+        let infos = test_accounts.infos(&[2, 5, 6, 9, 10]);
+        let accounts = LeaseInit {
+            lease: infos[0].clone(),
+            queue: infos[1].clone(),
+            aggregator: infos[2].clone(),
+            funder: Account::try_from(&infos[3]).unwrap(),
+            payer: infos[4].clone(),
+            system_program: infos[5].clone(),
+            token_program: infos[6].clone(),
+            owner: infos[7].clone(),
+            escrow: Account::try_from(&infos[8]).unwrap(),
+            program_state: infos[9].clone(),
+            mint: infos[10].clone(),
+        };
+        let params = LeaseInitParams {
+            load_amount: 1,
+            withdraw_authority: keys[7],
+            lease_bump: 2,
+            state_bump: 3,
+            wallet_bumps: vec![4, 5],
+        };
+
+        let ix = accounts
+            .get_instruction(crate::SWITCHBOARD_V2_DEVNET, params.clone(), &infos[11..])
+            .unwrap();
+        let mut expected = LeaseInit::discriminator().to_vec();
+        expected.extend(params.try_to_vec().unwrap());
+        assert_eq!(ix.data, expected);
+        assert_eq!(
+            ix.accounts
+                .iter()
+                .map(|meta| meta.pubkey)
+                .collect::<Vec<_>>(),
+            keys
+        );
+        // the payer and owner always sign, the job wallets are credited
+        assert_eq!(
+            ix.accounts
+                .iter()
+                .map(|meta| (meta.is_signer, meta.is_writable))
+                .collect::<Vec<_>>(),
+            vec![
+                (false, true),
+                (false, true),
+                (false, false),
+                (false, true),
+                (true, true),
+                (false, false),
+                (false, false),
+                (true, true),
+                (false, true),
+                (false, false),
+                (false, false),
+                (false, true),
+                (false, true),
+            ]
+        );
+
+        // one wallet per bump
+        assert_eq!(
+            accounts
+                .get_instruction(crate::SWITCHBOARD_V2_DEVNET, params, &infos[11..12])
+                .unwrap_err(),
+            error!(SwitchboardError::LeaseJobWalletsMismatch)
+        );
+    }
+
+    #[test]
+    fn test_lease_extend_instruction() {
+        // funder and escrow are token accounts, followed by the wallets of two jobs
+        let mut test_accounts = TestAccounts::new(11, &[3, 5, 9, 10], 4);
+        let keys = test_accounts.keys.clone();
+        let infos = test_accounts.infos(&[1, 2, 6, 7, 8]);
+        let accounts = LeaseExtend {
+            lease: infos[0].clone(),
+            aggregator: infos[1].clone(),
+            queue: infos[2].clone(),
+            funder: Account::try_from(&infos[3]).unwrap(),
+            owner: infos[4].clone(),
+            escrow: Account::try_from(&infos[5]).unwrap(),
+            token_program: infos[6].clone(),
+            program_state: infos[7].clone(),
+            mint: infos[8].clone(),
+        };
+        let params = LeaseExtendParams {
+            load_amount: 1,
+            lease_bump: 2,
+            state_bump: 3,
+            wallet_bumps: vec![4, 5],
+        };
+
+        let ix = accounts
+            .get_instruction(crate::SWITCHBOARD_V2_DEVNET, params.clone(), &infos[9..])
+            .unwrap();
+        let mut expected = LeaseExtend::discriminator().to_vec();
+        expected.extend(params.try_to_vec().unwrap());
+        assert_eq!(ix.data, expected);
+        assert_eq!(
+            ix.accounts
+                .iter()
+                .map(|meta| meta.pubkey)
+                .collect::<Vec<_>>(),
+            keys
+        );
+        assert!(ix.accounts[4].is_signer);
+        assert!(ix.accounts[9..].iter().all(|meta| meta.is_writable));
+
+        assert_eq!(
+            accounts
+                .get_instruction(crate::SWITCHBOARD_V2_DEVNET, params, &[])
+                .unwrap_err(),
+            error!(SwitchboardError::LeaseJobWalletsMismatch)
+        );
+    }
+
+    #[test]
+    fn test_lease_withdraw_instruction() {
+        // escrow and withdraw account are token accounts
+        let mut test_accounts = TestAccounts::new(9, &[1, 5], 7);
+        let keys = test_accounts.keys.clone();
+        let infos = test_accounts.infos(&[]);
+        let accounts = LeaseWithdraw {
+            lease: infos[0].clone(),
+            escrow: Account::try_from(&infos[1]).unwrap(),
+            aggregator: infos[2].clone(),
+            queue: infos[3].clone(),
+            withdraw_authority: infos[4].clone(),
+            withdraw_account: Account::try_from(&infos[5]).unwrap(),
+            token_program: infos[6].clone(),
+            program_state: infos[7].clone(),
+            mint: infos[8].clone(),
+        };
+
+        let ix = accounts
+            .get_instruction(
+                crate::SWITCHBOARD_V2_DEVNET,
+                LeaseWithdrawParams {
+                    state_bump: 1,
+                    lease_bump: 2,
+                    amount: 3,
+                },
+            )
+            .unwrap();
+        let mut expected = vec![186, 41, 100, 248, 234, 81, 61, 169, 1, 2];
+        expected.extend_from_slice(&3u64.to_le_bytes());
+        assert_eq!(ix.data, expected);
+        assert_eq!(
+            ix.accounts
+                .iter()
+                .map(|meta| meta.pubkey)
+                .collect::<Vec<_>>(),
+            keys
+        );
+        // the withdraw authority always signs
+        assert_eq!(
+            ix.accounts
+                .iter()
+                .map(|meta| meta.is_signer)
+                .collect::<Vec<_>>(),
+            vec![false, false, false, false, true, false, false, false, false]
+        );
+    }
+}
@@ -1,15 +1,43 @@
 use anchor_spl::token::TokenAccount;
 
 pub mod aggregator;
+pub mod aggregator_config;
+pub mod cadence;
+#[cfg(feature = "client")]
+pub mod client;
+pub mod cpi;
+pub mod crank;
 pub mod decimal;
+pub mod decoder;
+#[cfg(feature = "ecvrf")]
+pub mod ecvrf;
 pub mod error;
 pub mod history_buffer;
+pub mod indicators;
+mod instruction;
+pub mod lease;
+pub mod ohlc;
+pub mod oracle_queue;
+pub mod permission;
 pub mod vrf;
+pub mod vrf_random;
 
 pub use aggregator::AggregatorAccountData;
-pub use history_buffer::AggregatorHistoryBuffer;
+pub use aggregator::AggregatorOpenRound;
+pub use cadence::UpdateCadenceReport;
+pub use crank::{CrankAccountData, CrankRow};
+pub use decoder::{DecodedInstruction, SwitchboardInstruction};
+pub use history_buffer::{
+    AggregatorHistory, AggregatorHistoryBuffer, OwnedAggregatorHistoryBuffer,
+};
+pub use ohlc::{GapFill, OhlcCandle, OhlcConfig};
+pub use oracle_queue::OracleQueueAccountData;
+pub use permission::SwitchboardPermission;
 pub use vrf::VrfAccountData;
+pub use vrf::VrfInit;
 pub use vrf::VrfRequestRandomness;
+pub use vrf::VrfSetCallback;
+pub use vrf_random::VrfRng;
 
 /// Mainnet program id for Switchboard v2. Prints out as "SW1TCH7qEPTdLsDHRgPuMQjbQxKdH2aBStViMFnt64f"
 pub const SWITCHBOARD_V2_MAINNET: solana_program::pubkey::Pubkey =
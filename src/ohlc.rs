@@ -0,0 +1,277 @@
+use super::decimal::SwitchboardDecimal;
+use super::error::SwitchboardError;
+use super::history_buffer::{chronological_rows, decode_history_rows, AggregatorHistoryRow};
+use anchor_lang::prelude::*;
+use rust_decimal::Decimal;
+use std::convert::TryInto;
+
+/// Most candles a single resample can produce, so a wide span with a small interval can't exhaust
+/// memory or compute.
+pub const MAX_OHLC_CANDLES: i64 = 10_000;
+
+/// How to fill candles for intervals without any history buffer updates.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum GapFill {
+    /// Repeat the previous close as open, high, low and close.
+    CarryForward,
+    /// Leave the candle zeroed. Empty candles can be detected with `OhlcCandle::is_empty`.
+    Empty,
+}
+
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub struct OhlcConfig {
+    // Inclusive timestamp the first candle starts at.
+    pub start_timestamp: i64,
+    // Exclusive timestamp the last candle ends at.
+    pub end_timestamp: i64,
+    // Width of each candle in seconds.
+    pub interval: i64,
+    pub gap_fill: GapFill,
+}
+
+#[derive(Copy, Clone, Debug, Default, Eq, PartialEq)]
+pub struct OhlcCandle {
+    // Timestamp the candle starts at.
+    pub timestamp: i64,
+    pub open: SwitchboardDecimal,
+    pub high: SwitchboardDecimal,
+    pub low: SwitchboardDecimal,
+    pub close: SwitchboardDecimal,
+    // Number of history buffer rows in the candle, zero for gap filled candles.
+    pub count: u32,
+}
+
+impl OhlcCandle {
+    pub fn is_empty(&self) -> bool {
+        self.count == 0
+    }
+}
+
+struct Bucket {
+    candle: OhlcCandle,
+    high: Decimal,
+    low: Decimal,
+}
+
+/// Resample history buffer rows into fixed-interval OHLC candles. Rows are expected in
+/// chronological order, see `AggregatorHistoryBuffer::iter`. Rows before the start timestamp only
+/// seed the carried forward value. Fails if the config spans more than `MAX_OHLC_CANDLES` candles.
+pub fn resample_ohlc<'r, I>(rows: I, config: &OhlcConfig) -> anchor_lang::Result<Vec<OhlcCandle>>
+where
+    I: IntoIterator<Item = &'r AggregatorHistoryRow>,
+{
+    if config.interval <= 0 || config.end_timestamp <= config.start_timestamp {
+        return Err(error!(SwitchboardError::InvalidOhlcConfig));
+    }
+    let span = config
+        .end_timestamp
+        .checked_sub(config.start_timestamp)
+        .ok_or(error!(SwitchboardError::IntegerOverflowError))?;
+    let num_candles = (span / config.interval) + (span % config.interval != 0) as i64;
+    if num_candles > MAX_OHLC_CANDLES {
+        return Err(error!(SwitchboardError::InvalidOhlcConfig));
+    }
+
+    let mut buckets: Vec<Option<Bucket>> = Vec::new();
+    buckets.resize_with(num_candles as usize, || None);
+    let mut last_close: Option<SwitchboardDecimal> = None;
+
+    for row in rows {
+        let timestamp: i64 = row.timestamp;
+        let value: SwitchboardDecimal = row.value;
+        if timestamp < config.start_timestamp {
+            last_close = Some(value);
+            continue;
+        }
+        if timestamp >= config.end_timestamp {
+            continue;
+        }
+        let decimal: Decimal = value.try_into()?;
+        let idx = ((timestamp - config.start_timestamp) / config.interval) as usize;
+        match &mut buckets[idx] {
+            Some(bucket) => {
+                if decimal > bucket.high {
+                    bucket.high = decimal;
+                    bucket.candle.high = value;
+                }
+                if decimal < bucket.low {
+                    bucket.low = decimal;
+                    bucket.candle.low = value;
+                }
+                bucket.candle.close = value;
+                bucket.candle.count += 1;
+            }
+            empty => {
+                *empty = Some(Bucket {
+                    candle: OhlcCandle {
+                        timestamp: config.start_timestamp + idx as i64 * config.interval,
+                        open: value,
+                        high: value,
+                        low: value,
+                        close: value,
+                        count: 1,
+                    },
+                    high: decimal,
+                    low: decimal,
+                });
+            }
+        }
+    }
+
+    let mut candles = Vec::with_capacity(buckets.len());
+    for (idx, bucket) in buckets.into_iter().enumerate() {
+        let candle = match (bucket, config.gap_fill, last_close) {
+            (Some(bucket), _, _) => bucket.candle,
+            (None, GapFill::CarryForward, Some(close)) => OhlcCandle {
+                timestamp: config.start_timestamp + idx as i64 * config.interval,
+                open: close,
+                high: close,
+                low: close,
+                close,
+                count: 0,
+            },
+            (None, _, _) => OhlcCandle {
+                timestamp: config.start_timestamp + idx as i64 * config.interval,
+                ..Default::default()
+            },
+        };
+        if !candle.is_empty() {
+            last_close = Some(candle.close);
+        }
+        candles.push(candle);
+    }
+    Ok(candles)
+}
+
+/// Resample an owned history buffer snapshot, e.g. account data fetched over RPC.
+pub fn resample_ohlc_from_bytes(
+    data: &[u8],
+    config: &OhlcConfig,
+) -> anchor_lang::Result<Vec<OhlcCandle>> {
+    let (insertion_idx, rows) = decode_history_rows(data)?;
+    resample_ohlc(chronological_rows(rows, insertion_idx), config)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn row(timestamp: i64, value: f64) -> AggregatorHistoryRow {
+        AggregatorHistoryRow {
+            timestamp,
+            value: SwitchboardDecimal::from_f64(value),
+        }
+    }
+
+    fn config(gap_fill: GapFill) -> OhlcConfig {
+        OhlcConfig {
+            start_timestamp: 100,
+            end_timestamp: 140,
+            interval: 10,
+            gap_fill,
+        }
+    }
+
+    fn rows() -> Vec<AggregatorHistoryRow> {
+        vec![
+            row(95, 9.0),
+            row(100, 10.0),
+            row(103, 12.0),
+            row(105, 8.0),
+            row(109, 11.0),
+            row(131, 20.0),
+            row(140, 30.0),
+        ]
+    }
+
+    #[test]
+    fn test_resample_ohlc_carry_forward() {
+        let candles = resample_ohlc(rows().iter(), &config(GapFill::CarryForward)).unwrap();
+        assert_eq!(candles.len(), 4);
+
+        assert_eq!(candles[0].timestamp, 100);
+        assert_eq!(candles[0].open, SwitchboardDecimal::from_f64(10.0));
+        assert_eq!(candles[0].high, SwitchboardDecimal::from_f64(12.0));
+        assert_eq!(candles[0].low, SwitchboardDecimal::from_f64(8.0));
+        assert_eq!(candles[0].close, SwitchboardDecimal::from_f64(11.0));
+        assert_eq!(candles[0].count, 4);
+
+        for candle in candles[1..3].iter() {
+            assert!(candle.is_empty());
+            assert_eq!(candle.open, SwitchboardDecimal::from_f64(11.0));
+            assert_eq!(candle.close, SwitchboardDecimal::from_f64(11.0));
+        }
+        assert_eq!(candles[2].timestamp, 120);
+
+        assert_eq!(candles[3].open, SwitchboardDecimal::from_f64(20.0));
+        assert_eq!(candles[3].count, 1);
+    }
+
+    #[test]
+    fn test_resample_ohlc_empty() {
+        let candles = resample_ohlc(rows().iter(), &config(GapFill::Empty)).unwrap();
+        assert_eq!(
+            candles[1],
+            OhlcCandle {
+                timestamp: 110,
+                ..Default::default()
+            }
+        );
+        assert_eq!(candles[3].close, SwitchboardDecimal::from_f64(20.0));
+    }
+
+    #[test]
+    fn test_resample_ohlc_seeds_from_prior_row() {
+        let mut config = config(GapFill::CarryForward);
+        config.start_timestamp = 96;
+        config.end_timestamp = 100;
+        let candles = resample_ohlc(rows().iter(), &config).unwrap();
+        assert_eq!(candles.len(), 1);
+        assert_eq!(candles[0].close, SwitchboardDecimal::from_f64(9.0));
+        assert!(candles[0].is_empty());
+    }
+
+    #[test]
+    fn test_resample_ohlc_invalid_config() {
+        let mut config = config(GapFill::Empty);
+        config.interval = 0;
+        assert!(resample_ohlc(rows().iter(), &config).is_err());
+        config.interval = 10;
+        config.end_timestamp = config.start_timestamp;
+        assert!(resample_ohlc(rows().iter(), &config).is_err());
+
+        config.end_timestamp = config.start_timestamp + MAX_OHLC_CANDLES * config.interval;
+        assert_eq!(
+            resample_ohlc(rows().iter(), &config).unwrap().len(),
+            MAX_OHLC_CANDLES as usize
+        );
+        config.end_timestamp += 1;
+        assert!(resample_ohlc(rows().iter(), &config).is_err());
+        config.interval = 1;
+        config.end_timestamp = i64::MAX;
+        assert!(resample_ohlc(rows().iter(), &config).is_err());
+    }
+
+    #[test]
+    fn test_resample_ohlc_from_bytes() {
+        // circular buffer with the newest row at index 1
+        let ring = [
+            row(109, 11.0),
+            row(131, 20.0),
+            row(100, 10.0),
+            row(103, 12.0),
+        ];
+        let mut data = b"BUFFERxx".to_vec();
+        data.extend_from_slice(&1u32.to_le_bytes());
+        data.extend_from_slice(bytemuck::cast_slice(&ring));
+
+        let candles = resample_ohlc_from_bytes(&data, &config(GapFill::CarryForward)).unwrap();
+        assert_eq!(candles[0].open, SwitchboardDecimal::from_f64(10.0));
+        assert_eq!(candles[0].close, SwitchboardDecimal::from_f64(11.0));
+        assert_eq!(candles[0].count, 3);
+        assert_eq!(candles[3].close, SwitchboardDecimal::from_f64(20.0));
+
+        data[0] = 0;
+        assert!(resample_ohlc_from_bytes(&data, &config(GapFill::Empty)).is_err());
+    }
+}
@@ -0,0 +1,138 @@
+use super::decimal::SwitchboardDecimal;
+use super::error::SwitchboardError;
+use anchor_lang::prelude::*;
+use bytemuck::{Pod, Zeroable};
+use solana_program::pubkey::Pubkey;
+use std::cell::Ref;
+
+#[zero_copy]
+#[repr(C, packed)]
+#[derive(Pod, Zeroable)]
+pub struct OracleQueueAccountData {
+    pub name: [u8; 32],
+    pub metadata: [u8; 64],
+    pub authority: Pubkey,
+    // Seconds an oracle can go without a heartbeat before it is removed from the queue.
+    pub oracle_timeout: u32,
+    // Tokens paid to each oracle per update or VRF verification.
+    pub reward: u64,
+    pub min_stake: u64,
+    // bool, see `is_slashing_enabled`
+    pub slashing_enabled: u8,
+    pub variance_tolerance_multiplier: SwitchboardDecimal,
+    pub feed_probation_period: u32,
+    pub curr_idx: u32,
+    pub size: u32,
+    pub gc_idx: u32,
+    pub consecutive_feed_failure_limit: u64,
+    pub consecutive_oracle_failure_limit: u64,
+    // bool, see `unpermissioned_feeds_enabled`
+    pub unpermissioned_feeds_enabled: u8,
+    // bool, see `unpermissioned_vrf_enabled`
+    pub unpermissioned_vrf_enabled: u8,
+    pub curator_reward_cut: SwitchboardDecimal,
+    // bool, see `lock_lease_funding`
+    pub lock_lease_funding: u8,
+    pub mint: Pubkey,
+    // bool, see `enable_buffer_relayers`
+    pub enable_buffer_relayers: u8,
+    pub _ebuf: [u8; 968], // Buffer for future info
+    pub max_size: u32,
+    pub data_buffer: Pubkey,
+}
+
+#[derive(Clone, Debug, AnchorSerialize, AnchorDeserialize)]
+pub struct OracleHeartbeatParams {
+    pub permission_bump: u8,
+}
+
+impl OracleQueueAccountData {
+    pub fn new<'info>(
+        oracle_queue: &'info AccountInfo,
+    ) -> anchor_lang::Result<Ref<'info, OracleQueueAccountData>> {
+        let data = oracle_queue.try_borrow_data()?;
+
+        let mut disc_bytes = [0u8; 8];
+        disc_bytes.copy_from_slice(&data[..8]);
+        if disc_bytes != OracleQueueAccountData::discriminator() {
+            msg!("{:?}", disc_bytes);
+            return Err(error!(SwitchboardError::AccountDiscriminatorMismatch));
+        }
+
+        Ok(Ref::map(data, |data| {
+            bytemuck::from_bytes(&data[8..8 + std::mem::size_of::<OracleQueueAccountData>()])
+        }))
+    }
+
+    pub fn is_slashing_enabled(&self) -> bool {
+        self.slashing_enabled != 0
+    }
+
+    pub fn unpermissioned_feeds_enabled(&self) -> bool {
+        self.unpermissioned_feeds_enabled != 0
+    }
+
+    pub fn unpermissioned_vrf_enabled(&self) -> bool {
+        self.unpermissioned_vrf_enabled != 0
+    }
+
+    pub fn lock_lease_funding(&self) -> bool {
+        self.lock_lease_funding != 0
+    }
+
+    pub fn enable_buffer_relayers(&self) -> bool {
+        self.enable_buffer_relayers != 0
+    }
+
+    /// Tokens a VRF request on this queue costs, paying each oracle in the batch the queue reward.
+    pub fn vrf_request_cost(&self, batch_size: u32) -> anchor_lang::Result<u64> {
+        let reward = self.reward;
+        reward
+            .checked_mul(batch_size as u64)
+            .ok_or(error!(SwitchboardError::IntegerOverflowError))
+    }
+
+    fn discriminator() -> [u8; 8] {
+        [164, 207, 200, 51, 199, 113, 35, 109]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_oracle_queue_decoding() {
+        let mut queue = OracleQueueAccountData::zeroed();
+        queue.reward = 2_000_000;
+        queue.unpermissioned_vrf_enabled = 1;
+        queue.data_buffer = Pubkey::new_unique();
+
+        let mut data = OracleQueueAccountData::discriminator().to_vec();
+        data.extend_from_slice(bytemuck::bytes_of(&queue));
+        let key = Pubkey::new_unique();
+        let mut lamports = 0;
+        let queue_account_info = AccountInfo::new(
+            &key,
+            false,
+            false,
+            &mut lamports,
+            &mut data,
+            &crate::SWITCHBOARD_V2_DEVNET,
+            false,
+            0,
+        );
+        let decoded = OracleQueueAccountData::new(&queue_account_info).unwrap();
+        assert!(decoded.unpermissioned_vrf_enabled());
+        assert!(!decoded.is_slashing_enabled());
+        let data_buffer = decoded.data_buffer;
+        assert_eq!(data_buffer, queue.data_buffer);
+        assert_eq!(decoded.vrf_request_cost(3).unwrap(), 6_000_000);
+
+        drop(decoded);
+        let mut data = queue_account_info.try_borrow_mut_data().unwrap();
+        data[0] = 0;
+        drop(data);
+        assert!(OracleQueueAccountData::new(&queue_account_info).is_err());
+    }
+}
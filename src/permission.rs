@@ -0,0 +1,286 @@
+//! Queue permissions. A queue authority grants an aggregator, VRF account or oracle the right to
+//! use its queue through a permission account.
+use super::instruction::{build_instruction, invoke_instruction};
+use anchor_lang::prelude::*;
+use solana_program::instruction::Instruction;
+use solana_program::pubkey::Pubkey;
+
+/// A permission the queue authority can grant. Serialized by variant index, see `bits` for the
+/// flag stored in the permission account.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, AnchorSerialize, AnchorDeserialize)]
+pub enum SwitchboardPermission {
+    PermitOracleHeartbeat,
+    PermitOracleQueueUsage,
+    PermitVrfRequests,
+}
+
+impl SwitchboardPermission {
+    pub fn bits(&self) -> u32 {
+        match self {
+            SwitchboardPermission::PermitOracleHeartbeat => 1 << 0,
+            SwitchboardPermission::PermitOracleQueueUsage => 1 << 1,
+            SwitchboardPermission::PermitVrfRequests => 1 << 2,
+        }
+    }
+
+    /// Whether this permission is set in a permission account's `permissions` flags.
+    pub fn is_set(&self, permissions: u32) -> bool {
+        permissions & self.bits() != 0
+    }
+}
+
+/// Create the permission account for `grantee` on `granter`, with no permissions enabled.
+#[derive(Accounts)]
+#[instruction(params: PermissionInitParams)] // rpc parameters hint
+pub struct PermissionInit<'info> {
+    #[account(
+        mut,
+        seeds = [
+            b"PermissionAccountData",
+            authority.key().as_ref(),
+            granter.key().as_ref(),
+            grantee.key().as_ref()
+        ],
+        bump = params.permission_bump
+    )]
+    pub permission: AccountInfo<'info>,
+    pub authority: AccountInfo<'info>,
+    pub granter: AccountInfo<'info>,
+    pub grantee: AccountInfo<'info>,
+    #[account(mut, signer)]
+    pub payer: AccountInfo<'info>,
+    pub system_program: AccountInfo<'info>,
+}
+
+#[derive(Clone, Debug, AnchorSerialize, AnchorDeserialize)]
+pub struct PermissionInitParams {
+    pub permission_bump: u8,
+}
+
+impl<'info> PermissionInit<'info> {
+    pub(crate) fn discriminator() -> [u8; 8] {
+        [177, 116, 201, 233, 16, 2, 11, 179]
+    }
+
+    pub fn get_instruction(
+        &self,
+        program_id: Pubkey,
+        params: PermissionInitParams,
+    ) -> anchor_lang::Result<Instruction> {
+        build_instruction(
+            program_id,
+            PermissionInit::discriminator(),
+            params,
+            self.to_account_metas(None),
+        )
+    }
+
+    pub fn invoke(
+        &self,
+        program: AccountInfo<'info>,
+        permission_bump: u8,
+    ) -> anchor_lang::Result<()> {
+        let instruction =
+            self.get_instruction(*program.key, PermissionInitParams { permission_bump })?;
+        let account_infos = self.to_account_infos();
+
+        invoke_instruction(&instruction, &account_infos, None)
+    }
+
+    pub fn invoke_signed(
+        &self,
+        program: AccountInfo<'info>,
+        permission_bump: u8,
+        signer_seeds: &[&[&[u8]]],
+    ) -> anchor_lang::Result<()> {
+        let instruction =
+            self.get_instruction(*program.key, PermissionInitParams { permission_bump })?;
+        let account_infos = self.to_account_infos();
+
+        invoke_instruction(&instruction, &account_infos, Some(signer_seeds))
+    }
+
+    fn to_account_infos(&self) -> Vec<AccountInfo<'info>> {
+        vec![
+            self.permission.clone(),
+            self.authority.clone(),
+            self.granter.clone(),
+            self.grantee.clone(),
+            self.payer.clone(),
+            self.system_program.clone(),
+        ]
+    }
+
+    #[allow(unused_variables)]
+    fn to_account_metas(&self, is_signer: Option<bool>) -> Vec<AccountMeta> {
+        vec![
+            AccountMeta {
+                pubkey: *self.permission.key,
+                is_signer: self.permission.is_signer,
+                is_writable: self.permission.is_writable,
+            },
+            AccountMeta {
+                pubkey: *self.authority.key,
+                is_signer: self.authority.is_signer,
+                is_writable: self.authority.is_writable,
+            },
+            AccountMeta {
+                pubkey: *self.granter.key,
+                is_signer: self.granter.is_signer,
+                is_writable: self.granter.is_writable,
+            },
+            AccountMeta {
+                pubkey: *self.grantee.key,
+                is_signer: self.grantee.is_signer,
+                is_writable: self.grantee.is_writable,
+            },
+            AccountMeta {
+                pubkey: *self.payer.key,
+                is_signer: true, // overwrite, has to sign
+                is_writable: self.payer.is_writable,
+            },
+            AccountMeta {
+                pubkey: *self.system_program.key,
+                is_signer: self.system_program.is_signer,
+                is_writable: self.system_program.is_writable,
+            },
+        ]
+    }
+}
+
+/// Enable or disable a permission. The queue authority must sign, e.g. with its PDA seeds.
+#[derive(Accounts)]
+#[instruction(params: PermissionSetParams)] // rpc parameters hint
+pub struct PermissionSet<'info> {
+    #[account(mut)]
+    pub permission: AccountInfo<'info>,
+    #[account(signer)]
+    pub authority: AccountInfo<'info>,
+}
+
+#[derive(Clone, Debug, AnchorSerialize, AnchorDeserialize)]
+pub struct PermissionSetParams {
+    pub permission: SwitchboardPermission,
+    pub enable: bool,
+}
+
+impl<'info> PermissionSet<'info> {
+    pub(crate) fn discriminator() -> [u8; 8] {
+        [211, 122, 185, 120, 129, 182, 55, 103]
+    }
+
+    pub fn get_instruction(
+        &self,
+        program_id: Pubkey,
+        params: PermissionSetParams,
+    ) -> anchor_lang::Result<Instruction> {
+        build_instruction(
+            program_id,
+            PermissionSet::discriminator(),
+            params,
+            self.to_account_metas(None),
+        )
+    }
+
+    pub fn invoke(
+        &self,
+        program: AccountInfo<'info>,
+        permission: SwitchboardPermission,
+        enable: bool,
+    ) -> anchor_lang::Result<()> {
+        let instruction =
+            self.get_instruction(*program.key, PermissionSetParams { permission, enable })?;
+        let account_infos = self.to_account_infos();
+
+        invoke_instruction(&instruction, &account_infos, None)
+    }
+
+    pub fn invoke_signed(
+        &self,
+        program: AccountInfo<'info>,
+        permission: SwitchboardPermission,
+        enable: bool,
+        signer_seeds: &[&[&[u8]]],
+    ) -> anchor_lang::Result<()> {
+        let instruction =
+            self.get_instruction(*program.key, PermissionSetParams { permission, enable })?;
+        let account_infos = self.to_account_infos();
+
+        invoke_instruction(&instruction, &account_infos, Some(signer_seeds))
+    }
+
+    fn to_account_infos(&self) -> Vec<AccountInfo<'info>> {
+        vec![self.permission.clone(), self.authority.clone()]
+    }
+
+    #[allow(unused_variables)]
+    fn to_account_metas(&self, is_signer: Option<bool>) -> Vec<AccountMeta> {
+        vec![
+            AccountMeta {
+                pubkey: *self.permission.key,
+                is_signer: self.permission.is_signer,
+                is_writable: self.permission.is_writable,
+            },
+            AccountMeta {
+                pubkey: *self.authority.key,
+                is_signer: true, // overwrite, authority has to sign
+                is_writable: self.authority.is_writable,
+            },
+        ]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_permission_set_instruction() {
+        let keys = [Pubkey::new_unique(), Pubkey::new_unique()];
+        let mut lamports = [0u64; 2];
+        let mut data = [vec![], vec![]];
+        let [permission_lamports, authority_lamports] = &mut lamports;
+        let [permission_data, authority_data] = &mut data;
+        let accounts = PermissionSet {
+            permission: AccountInfo::new(
+                &keys[0],
+                false,
+                true,
+                permission_lamports,
+                permission_data,
+                &keys[0],
+                false,
+                0,
+            ),
+            authority: AccountInfo::new(
+                &keys[1],
+                false,
+                false,
+                authority_lamports,
+                authority_data,
+                &keys[1],
+                false,
+                0,
+            ),
+        };
+
+        let ix = accounts
+            .get_instruction(
+                crate::SWITCHBOARD_V2_DEVNET,
+                PermissionSetParams {
+                    permission: SwitchboardPermission::PermitVrfRequests,
+                    enable: true,
+                },
+            )
+            .unwrap();
+        // the enum is encoded by variant index, not by its flag
+        assert_eq!(ix.data, vec![211, 122, 185, 120, 129, 182, 55, 103, 2, 1]);
+        assert!(ix.accounts[0].is_writable);
+        assert!(ix.accounts[1].is_signer);
+
+        let permission = SwitchboardPermission::PermitOracleQueueUsage;
+        assert_eq!(permission.bits(), 2);
+        assert!(permission.is_set(0b011));
+        assert!(!permission.is_set(0b101));
+    }
+}
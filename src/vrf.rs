@@ -1,17 +1,23 @@
 #![allow(non_snake_case)]
 use super::error::SwitchboardError;
+use super::instruction::{build_instruction, invoke_instruction};
+use super::oracle_queue::OracleQueueAccountData;
 #[allow(unaligned_references)]
 use crate::*;
 use anchor_lang::prelude::*;
 use bytemuck::{Pod, Zeroable};
 use solana_program::account_info::AccountInfo;
 use solana_program::instruction::Instruction;
-use solana_program::program::{invoke, invoke_signed};
 use solana_program::pubkey::Pubkey;
+use solana_program::sysvar::instructions::{
+    load_current_index_checked, load_instruction_at_checked,
+};
 use std::cell::Ref;
+use std::convert::TryFrom;
 
 #[zero_copy]
-#[repr(packed)]
+#[repr(C, packed)]
+#[derive(Pod, Zeroable)]
 pub struct EcvrfProofZC {
     pub Gamma: EdwardsPointZC, // RistrettoPoint
     pub c: Scalar,
@@ -19,7 +25,7 @@ pub struct EcvrfProofZC {
 }
 impl Default for EcvrfProofZC {
     fn default() -> Self {
-        unsafe { std::mem::zeroed() }
+        Zeroable::zeroed()
     }
 }
 
@@ -27,7 +33,8 @@ impl Default for EcvrfProofZC {
 /// represents an element of \\(\mathbb Z / \ell\\).
 #[allow(dead_code)]
 #[zero_copy]
-#[repr(packed)]
+#[repr(C, packed)]
+#[derive(Pod, Zeroable)]
 pub struct Scalar {
     /// `bytes` is a little-endian byte encoding of an integer representing a scalar modulo the
     /// group order.
@@ -43,8 +50,6 @@ pub struct Scalar {
     //     make the Scalar constructors const fns and use those instead.
     pub(crate) bytes: [u8; 32],
 }
-unsafe impl Pod for Scalar {}
-unsafe impl Zeroable for Scalar {}
 
 /// A `FieldElement51` represents an element of the field
 /// \\( \mathbb Z / (2\^{255} - 19)\\).
@@ -61,24 +66,21 @@ unsafe impl Zeroable for Scalar {}
 ///
 /// The backend-specific type `FieldElement51` should not be used
 /// outside of the `curve25519_dalek::field` module.
-#[derive(Copy, Clone, Default)]
+#[derive(Copy, Clone, Default, Pod, Zeroable)]
 #[repr(C)]
 pub struct FieldElement51(pub(crate) [u64; 5]);
-unsafe impl Pod for FieldElement51 {}
-unsafe impl Zeroable for FieldElement51 {}
 
 #[zero_copy]
-#[repr(packed)]
+#[repr(C, packed)]
+#[derive(Pod, Zeroable)]
 pub struct FieldElementZC {
     pub(crate) bytes: [u64; 5],
 }
 impl Default for FieldElementZC {
     fn default() -> Self {
-        unsafe { std::mem::zeroed() }
+        Zeroable::zeroed()
     }
 }
-unsafe impl Pod for FieldElementZC {}
-unsafe impl Zeroable for FieldElementZC {}
 impl Into<FieldElementZC> for FieldElement51 {
     fn into(self) -> FieldElementZC {
         FieldElementZC { bytes: self.0 }
@@ -98,7 +100,7 @@ impl Into<FieldElement51> for FieldElementZC {
 /// More details on the relationships between the different curve models
 /// can be found in the module-level documentation.
 #[allow(missing_docs)]
-#[derive(Copy, Clone)]
+#[derive(Copy, Clone, Pod, Zeroable)]
 #[repr(C)]
 pub struct CompletedPoint {
     pub X: FieldElement51,
@@ -107,7 +109,8 @@ pub struct CompletedPoint {
     pub T: FieldElement51,
 }
 #[zero_copy]
-#[repr(packed)]
+#[repr(C, packed)]
+#[derive(Pod, Zeroable)]
 pub struct CompletedPointZC {
     pub X: FieldElementZC,
     pub Y: FieldElementZC,
@@ -116,11 +119,9 @@ pub struct CompletedPointZC {
 }
 impl Default for CompletedPointZC {
     fn default() -> Self {
-        unsafe { std::mem::zeroed() }
+        Zeroable::zeroed()
     }
 }
-unsafe impl Pod for CompletedPoint {}
-unsafe impl Zeroable for CompletedPoint {}
 impl Into<CompletedPointZC> for CompletedPoint {
     fn into(self) -> CompletedPointZC {
         CompletedPointZC {
@@ -143,7 +144,7 @@ impl Into<CompletedPoint> for CompletedPointZC {
 }
 
 /// An `EdwardsPoint` represents a point on the Edwards form of Curve25519.
-#[derive(Copy, Clone)]
+#[derive(Copy, Clone, Pod, Zeroable)]
 #[repr(C)]
 pub struct EdwardsPoint {
     pub(crate) X: FieldElement51,
@@ -153,7 +154,8 @@ pub struct EdwardsPoint {
 }
 #[allow(dead_code)]
 #[zero_copy]
-#[repr(packed)]
+#[repr(C, packed)]
+#[derive(Pod, Zeroable)]
 pub struct EdwardsPointZC {
     pub(crate) X: FieldElementZC,
     pub(crate) Y: FieldElementZC,
@@ -162,7 +164,7 @@ pub struct EdwardsPointZC {
 }
 impl Default for EdwardsPointZC {
     fn default() -> Self {
-        unsafe { std::mem::zeroed() }
+        Zeroable::zeroed()
     }
 }
 
@@ -173,7 +175,7 @@ impl Default for EdwardsPointZC {
 ///
 /// More details on the relationships between the different curve models
 /// can be found in the module-level documentation.
-#[derive(Copy, Clone, Default)]
+#[derive(Copy, Clone, Default, Pod, Zeroable)]
 #[repr(C)]
 pub struct ProjectivePoint {
     pub X: FieldElement51,
@@ -181,7 +183,8 @@ pub struct ProjectivePoint {
     pub Z: FieldElement51,
 }
 #[zero_copy]
-#[repr(packed)]
+#[repr(C, packed)]
+#[derive(Pod, Zeroable)]
 pub struct ProjectivePointZC {
     pub(crate) X: FieldElementZC,
     pub(crate) Y: FieldElementZC,
@@ -189,11 +192,9 @@ pub struct ProjectivePointZC {
 }
 impl Default for ProjectivePointZC {
     fn default() -> Self {
-        unsafe { std::mem::zeroed() }
+        Zeroable::zeroed()
     }
 }
-unsafe impl Pod for ProjectivePoint {}
-unsafe impl Zeroable for ProjectivePoint {}
 impl Into<ProjectivePointZC> for ProjectivePoint {
     fn into(self) -> ProjectivePointZC {
         ProjectivePointZC {
@@ -214,7 +215,8 @@ impl Into<ProjectivePoint> for ProjectivePointZC {
 }
 
 #[zero_copy]
-#[repr(packed)]
+#[repr(C, packed)]
+#[derive(Pod, Zeroable)]
 pub struct EcvrfIntermediate {
     pub r: FieldElementZC,
     pub N_s: FieldElementZC,
@@ -222,15 +224,15 @@ pub struct EcvrfIntermediate {
     pub t13: FieldElementZC,
     pub t15: FieldElementZC,
 }
-unsafe impl Pod for EcvrfIntermediate {}
-unsafe impl Zeroable for EcvrfIntermediate {}
 
 #[allow(non_snake_case)]
 #[zero_copy]
-#[repr(packed)]
+#[repr(C, packed)]
+#[derive(Pod, Zeroable)]
 pub struct VrfBuilder {
     pub producer: Pubkey,
-    pub status: VrfStatus,
+    // VrfStatus, see `get_status`
+    pub status: u8,
     pub repr_proof: [u8; 80],
     pub proof: EcvrfProofZC,
     pub Y_point: Pubkey,
@@ -265,26 +267,48 @@ pub struct VrfBuilder {
     pub m1: FieldElementZC,
     pub m2: FieldElementZC,
     pub tx_remaining: u32,
-    pub verified: bool,
+    // bool, see `is_verified`
+    pub verified: u8,
     pub result: [u8; 32],
 }
 impl Default for VrfBuilder {
     fn default() -> Self {
-        unsafe { std::mem::zeroed() }
+        Zeroable::zeroed()
+    }
+}
+impl VrfBuilder {
+    pub fn get_status(&self) -> VrfStatus {
+        self.status.into()
+    }
+
+    pub fn is_verified(&self) -> bool {
+        self.verified != 0
     }
 }
 
 #[zero_copy]
-#[repr(packed)]
+#[repr(C, packed)]
+#[derive(Pod, Zeroable)]
 pub struct AccountMetaZC {
     pub pubkey: Pubkey,
-    pub is_signer: bool,
-    pub is_writable: bool,
+    // bool, see `is_signer`
+    pub is_signer: u8,
+    // bool, see `is_writable`
+    pub is_writable: u8,
+}
+impl AccountMetaZC {
+    pub fn is_signer(&self) -> bool {
+        self.is_signer != 0
+    }
+
+    pub fn is_writable(&self) -> bool {
+        self.is_writable != 0
+    }
 }
 
 #[zero_copy]
-#[repr(packed)]
-#[derive(AnchorSerialize, AnchorDeserialize)]
+#[repr(C, packed)]
+#[derive(Debug, AnchorSerialize, AnchorDeserialize)]
 pub struct AccountMetaBorsh {
     pub pubkey: Pubkey,
     pub is_signer: bool,
@@ -292,7 +316,8 @@ pub struct AccountMetaBorsh {
 }
 
 #[zero_copy]
-#[repr(packed)]
+#[repr(C, packed)]
+#[derive(Pod, Zeroable)]
 pub struct CallbackZC {
     pub program_id: Pubkey,
     pub accounts: [AccountMetaZC; 32],
@@ -302,19 +327,134 @@ pub struct CallbackZC {
 }
 impl Default for CallbackZC {
     fn default() -> Self {
-        unsafe { std::mem::zeroed() }
+        Zeroable::zeroed()
+    }
+}
+impl CallbackZC {
+    /// configured accounts, erroring if the stored length is out of bounds
+    pub fn get_accounts(&self) -> anchor_lang::Result<&[AccountMetaZC]> {
+        let accounts_len = self.accounts_len as usize;
+        if accounts_len > self.accounts.len() {
+            return Err(error!(SwitchboardError::VrfCallbackTooLargeError));
+        }
+        Ok(&self.accounts[..accounts_len])
+    }
+
+    /// configured instruction data, erroring if the stored length is out of bounds
+    pub fn get_ix_data(&self) -> anchor_lang::Result<&[u8]> {
+        let ix_data_len = self.ix_data_len as usize;
+        if ix_data_len > self.ix_data.len() {
+            return Err(error!(SwitchboardError::VrfCallbackTooLargeError));
+        }
+        Ok(&self.ix_data[..ix_data_len])
+    }
+
+    /// Check the callback invokes `program_id` with instruction data starting with
+    /// `discriminator` and exactly the `expected_accounts`, including signer and writable flags.
+    pub fn validate(
+        &self,
+        program_id: &Pubkey,
+        discriminator: &[u8],
+        expected_accounts: &[AccountMeta],
+    ) -> anchor_lang::Result<()> {
+        let callback_program_id = self.program_id;
+        if callback_program_id != *program_id {
+            return Err(error!(SwitchboardError::VrfCallbackProgramMismatch));
+        }
+        if !self.get_ix_data()?.starts_with(discriminator) {
+            return Err(error!(SwitchboardError::VrfCallbackDiscriminatorMismatch));
+        }
+        let accounts = self.get_accounts()?;
+        if accounts.len() != expected_accounts.len()
+            || accounts
+                .iter()
+                .zip(expected_accounts.iter())
+                .any(|(account, expected)| {
+                    let account: AccountMeta = (*account).into();
+                    account != *expected
+                })
+        {
+            return Err(error!(SwitchboardError::VrfCallbackAccountsMismatch));
+        }
+        Ok(())
     }
 }
 
-#[derive(AnchorSerialize, AnchorDeserialize)]
+#[derive(Clone, Debug, AnchorSerialize, AnchorDeserialize)]
 pub struct Callback {
     pub program_id: Pubkey,
     pub accounts: Vec<AccountMetaBorsh>,
     pub ix_data: Vec<u8>,
 }
+impl TryFrom<&Callback> for CallbackZC {
+    type Error = anchor_lang::error::Error;
+    fn try_from(value: &Callback) -> anchor_lang::Result<CallbackZC> {
+        let mut callback = CallbackZC::default();
+        if value.accounts.len() > callback.accounts.len()
+            || value.ix_data.len() > callback.ix_data.len()
+        {
+            return Err(error!(SwitchboardError::VrfCallbackTooLargeError));
+        }
+        let mut accounts = callback.accounts;
+        for (account, meta) in accounts.iter_mut().zip(value.accounts.iter()) {
+            *account = (*meta).into();
+        }
+        let mut ix_data = callback.ix_data;
+        ix_data[..value.ix_data.len()].copy_from_slice(&value.ix_data);
+
+        callback.program_id = value.program_id;
+        callback.accounts = accounts;
+        callback.accounts_len = value.accounts.len() as u32;
+        callback.ix_data = ix_data;
+        callback.ix_data_len = value.ix_data.len() as u32;
+        Ok(callback)
+    }
+}
+impl TryFrom<&CallbackZC> for Callback {
+    type Error = anchor_lang::error::Error;
+    fn try_from(value: &CallbackZC) -> anchor_lang::Result<Callback> {
+        Ok(Callback {
+            program_id: value.program_id,
+            accounts: value
+                .get_accounts()?
+                .iter()
+                .map(|account| (*account).into())
+                .collect(),
+            ix_data: value.get_ix_data()?.to_vec(),
+        })
+    }
+}
+impl From<AccountMetaZC> for AccountMetaBorsh {
+    fn from(meta: AccountMetaZC) -> Self {
+        AccountMetaBorsh {
+            pubkey: meta.pubkey,
+            is_signer: meta.is_signer(),
+            is_writable: meta.is_writable(),
+        }
+    }
+}
+impl From<AccountMetaBorsh> for AccountMetaZC {
+    fn from(meta: AccountMetaBorsh) -> Self {
+        AccountMetaZC {
+            pubkey: meta.pubkey,
+            is_signer: meta.is_signer as u8,
+            is_writable: meta.is_writable as u8,
+        }
+    }
+}
+impl From<AccountMetaZC> for AccountMeta {
+    fn from(meta: AccountMetaZC) -> Self {
+        AccountMeta {
+            pubkey: meta.pubkey,
+            is_signer: meta.is_signer(),
+            is_writable: meta.is_writable(),
+        }
+    }
+}
 
 #[zero_copy]
-#[repr(packed)]
+#[repr(C, packed)]
+#[derive(Pod, Zeroable)]
 pub struct VrfRound {
     pub alpha: [u8; 256],
     pub alpha_len: u32,
@@ -326,7 +466,7 @@ pub struct VrfRound {
 }
 impl Default for VrfRound {
     fn default() -> Self {
-        unsafe { std::mem::zeroed() }
+        Zeroable::zeroed()
     }
 }
 
@@ -338,13 +478,43 @@ pub enum VrfStatus {
     StatusVerified,
     StatusCallbackSuccess,
     StatusVerifyFailure,
+    // Status byte added by a newer program version than this crate knows about.
+    Unknown(u8),
+}
+impl From<u8> for VrfStatus {
+    fn from(status: u8) -> Self {
+        match status {
+            0 => VrfStatus::StatusNone,
+            1 => VrfStatus::StatusRequesting,
+            2 => VrfStatus::StatusVerifying,
+            3 => VrfStatus::StatusVerified,
+            4 => VrfStatus::StatusCallbackSuccess,
+            5 => VrfStatus::StatusVerifyFailure,
+            status => VrfStatus::Unknown(status),
+        }
+    }
+}
+impl From<VrfStatus> for u8 {
+    fn from(status: VrfStatus) -> Self {
+        match status {
+            VrfStatus::StatusNone => 0,
+            VrfStatus::StatusRequesting => 1,
+            VrfStatus::StatusVerifying => 2,
+            VrfStatus::StatusVerified => 3,
+            VrfStatus::StatusCallbackSuccess => 4,
+            VrfStatus::StatusVerifyFailure => 5,
+            VrfStatus::Unknown(status) => status,
+        }
+    }
 }
 
 // #[derive(Copy, Clone)]
 #[zero_copy]
-#[repr(packed)]
+#[repr(C, packed)]
+#[derive(Pod, Zeroable)]
 pub struct VrfAccountData {
-    pub status: VrfStatus,
+    // VrfStatus, see `get_status`
+    pub status: u8,
     pub counter: u128,
     pub authority: Pubkey,
     pub oracle_queue: Pubkey,
@@ -353,7 +523,8 @@ pub struct VrfAccountData {
     pub batch_size: u32,
     pub builders: [VrfBuilder; 8],
     pub builders_len: u32,
-    pub test_mode: bool,
+    // bool, see `is_test_mode`
+    pub test_mode: u8,
     // pub last_verified_round: VrfRound,
     pub current_round: VrfRound,
     //
@@ -361,10 +532,16 @@ pub struct VrfAccountData {
 }
 impl Default for VrfAccountData {
     fn default() -> Self {
-        unsafe { std::mem::zeroed() }
+        Zeroable::zeroed()
     }
 }
 
+// Switchboard instructions that invoke a VRF callback, with the index of the VRF account
+const VRF_CALLBACK_INSTRUCTIONS: [([u8; 8], usize); 2] = [
+    ([133, 190, 186, 48, 208, 164, 205, 34], 0), // vrf_prove_and_verify
+    ([53, 101, 13, 30, 245, 213, 102, 150], 0),  // vrf_verify
+];
+
 impl VrfAccountData {
     pub fn new<'info>(
         switchboard_vrf: &'info AccountInfo,
@@ -381,6 +558,61 @@ impl VrfAccountData {
         Ok(Ref::map(data, |data| bytemuck::from_bytes(&data[8..])))
     }
 
+    /// Load a VRF account from inside a VRF callback, checking the callback was invoked by the
+    /// Switchboard program for this account.
+    ///
+    /// The instruction currently being processed, read from the instructions sysvar, must be a
+    /// `switchboard_program_id` `vrf_prove_and_verify` or `vrf_verify` instruction with
+    /// `switchboard_vrf` as its VRF account. The VRF account must be owned by that program, have
+    /// a verified but not yet called back round, and a counter greater than `last_counter`, the
+    /// counter the caller recorded on its previous callback.
+    ///
+    /// The sysvar only exposes top level instructions, so this cannot tell the Switchboard
+    /// program's callback CPI apart from another program invoked while that instruction is being
+    /// processed. Callers should still validate the accounts they were passed.
+    pub fn authenticate_callback<'info>(
+        switchboard_vrf: &'info AccountInfo,
+        instructions_sysvar: &AccountInfo,
+        switchboard_program_id: &Pubkey,
+        last_counter: u128,
+    ) -> anchor_lang::Result<Ref<'info, VrfAccountData>> {
+        if switchboard_vrf.owner != switchboard_program_id {
+            return Err(error!(SwitchboardError::VrfCallbackUnauthorized));
+        }
+        let current_idx = load_current_index_checked(instructions_sysvar)?;
+        let current_ix = load_instruction_at_checked(current_idx as usize, instructions_sysvar)?;
+        if current_ix.program_id != *switchboard_program_id || current_ix.data.len() < 8 {
+            return Err(error!(SwitchboardError::VrfCallbackUnauthorized));
+        }
+        let vrf_idx = VRF_CALLBACK_INSTRUCTIONS
+            .iter()
+            .find(|(discriminator, _)| current_ix.data[..8] == discriminator[..])
+            .map(|(_, vrf_idx)| *vrf_idx)
+            .ok_or(error!(SwitchboardError::VrfCallbackUnauthorized))?;
+        match current_ix.accounts.get(vrf_idx) {
+            Some(account) if account.pubkey == *switchboard_vrf.key => {}
+            _ => return Err(error!(SwitchboardError::VrfCallbackUnauthorized)),
+        }
+
+        let vrf = VrfAccountData::new(switchboard_vrf)?;
+        if vrf.get_status() != VrfStatus::StatusVerified {
+            return Err(error!(SwitchboardError::VrfNotVerifiedError));
+        }
+        let counter = vrf.counter;
+        if counter <= last_counter {
+            return Err(error!(SwitchboardError::VrfStaleRoundError));
+        }
+        Ok(vrf)
+    }
+
+    pub fn get_status(&self) -> VrfStatus {
+        self.status.into()
+    }
+
+    pub fn is_test_mode(&self) -> bool {
+        self.test_mode != 0
+    }
+
     pub fn get_current_randomness_round_id(&self) -> u128 {
         self.counter
     }
@@ -392,13 +624,86 @@ impl VrfAccountData {
         Ok(self.current_round.result)
     }
 
+    /// see `CallbackZC::validate`
+    pub fn validate_callback(
+        &self,
+        program_id: &Pubkey,
+        discriminator: &[u8],
+        expected_accounts: &[AccountMeta],
+    ) -> anchor_lang::Result<()> {
+        self.callback
+            .validate(program_id, discriminator, expected_accounts)
+    }
+
+    /// randomness has been requested and the oracles have not finished verifying it
+    pub fn is_pending(&self) -> bool {
+        matches!(
+            self.get_status(),
+            VrfStatus::StatusRequesting | VrfStatus::StatusVerifying
+        )
+    }
+
+    /// the current round's proof was verified, whether or not the callback has run yet
+    pub fn is_verified(&self) -> bool {
+        matches!(
+            self.get_status(),
+            VrfStatus::StatusVerified | VrfStatus::StatusCallbackSuccess
+        )
+    }
+
+    pub fn callback_succeeded(&self) -> bool {
+        self.get_status() == VrfStatus::StatusCallbackSuccess
+    }
+
+    pub fn verify_failed(&self) -> bool {
+        self.get_status() == VrfStatus::StatusVerifyFailure
+    }
+
+    /// Check the current round was requested at or after the given slot and timestamp, and that
+    /// the account counter has reached `min_counter`. Pass the slot, timestamp and counter recorded
+    /// when issuing a request so a previous round's randomness is never consumed.
+    pub fn check_round_freshness(
+        &self,
+        min_request_slot: u64,
+        min_request_timestamp: i64,
+        min_counter: u128,
+    ) -> anchor_lang::Result<()> {
+        let request_slot = self.current_round.request_slot;
+        let request_timestamp = self.current_round.request_timestamp;
+        let counter = self.counter;
+        if request_slot < min_request_slot
+            || request_timestamp < min_request_timestamp
+            || counter < min_counter
+        {
+            return Err(error!(SwitchboardError::VrfStaleRoundError));
+        }
+        Ok(())
+    }
+
+    /// Verified result of a round that passes `check_round_freshness`.
+    pub fn get_fresh_result(
+        &self,
+        min_request_slot: u64,
+        min_request_timestamp: i64,
+        min_counter: u128,
+    ) -> anchor_lang::Result<[u8; 32]> {
+        self.check_round_freshness(min_request_slot, min_request_timestamp, min_counter)?;
+        if !self.is_verified() {
+            return Err(error!(SwitchboardError::VrfNotVerifiedError));
+        }
+        self.get_result()
+    }
+
+    /// Space to allocate for a new VRF account, including the account discriminator.
+    pub fn size() -> usize {
+        8 + std::mem::size_of::<VrfAccountData>()
+    }
+
     // Need to log and update to actual value
     fn discriminator() -> [u8; 8] {
         return [101, 35, 62, 239, 103, 151, 6, 18];
     }
 }
-unsafe impl Pod for VrfAccountData {}
-unsafe impl Zeroable for VrfAccountData {}
 
 #[derive(Accounts)]
 #[instruction(params: VrfRequestRandomnessParams)] // rpc parameters hint
@@ -434,30 +739,90 @@ pub struct VrfRequestRandomness<'info> {
     pub token_program: AccountInfo<'info>,
 }
 
-#[derive(Clone, AnchorSerialize, AnchorDeserialize)]
+#[derive(Clone, Debug, AnchorSerialize, AnchorDeserialize)]
 pub struct VrfRequestRandomnessParams {
     pub permission_bump: u8,
     pub state_bump: u8,
 }
 
 impl<'info> VrfRequestRandomness<'info> {
-    fn discriminator() -> [u8; 8] {
+    pub(crate) fn discriminator() -> [u8; 8] {
         return [230, 121, 14, 164, 28, 222, 117, 118];
     }
 
+    /// Pre-flight checks for the conditions a request would fail on, so callers can surface a
+    /// specific error instead of a failed CPI.
+    pub fn validate(
+        &self,
+        switchboard_program_id: &Pubkey,
+        params: &VrfRequestRandomnessParams,
+    ) -> anchor_lang::Result<()> {
+        let vrf = VrfAccountData::new(&self.vrf)?;
+        if vrf.is_pending() {
+            return Err(error!(SwitchboardError::VrfRequestInProgressError));
+        }
+        let vrf_queue = vrf.oracle_queue;
+        if vrf_queue != *self.oracle_queue.key {
+            return Err(error!(SwitchboardError::VrfQueueMismatch));
+        }
+        let queue = OracleQueueAccountData::new(&self.oracle_queue)?;
+        let queue_authority = queue.authority;
+        let queue_data_buffer = queue.data_buffer;
+        if queue_authority != *self.queue_authority.key
+            || queue_data_buffer != *self.data_buffer.key
+        {
+            return Err(error!(SwitchboardError::VrfQueueMismatch));
+        }
+
+        let vrf_escrow = vrf.escrow;
+        if vrf_escrow != self.escrow.key() || self.escrow.owner != *self.program_state.key {
+            return Err(error!(SwitchboardError::VrfEscrowMismatch));
+        }
+        let program_state = Pubkey::create_program_address(
+            &[b"STATE", &[params.state_bump]],
+            switchboard_program_id,
+        )
+        .map_err(|_| error!(SwitchboardError::ProgramStateMismatch))?;
+        if program_state != *self.program_state.key {
+            return Err(error!(SwitchboardError::ProgramStateMismatch));
+        }
+        let permission = Pubkey::create_program_address(
+            &[
+                b"PermissionAccountData",
+                self.queue_authority.key.as_ref(),
+                self.oracle_queue.key.as_ref(),
+                self.vrf.key.as_ref(),
+                &[params.permission_bump],
+            ],
+            switchboard_program_id,
+        )
+        .map_err(|_| error!(SwitchboardError::PermissionMismatch))?;
+        if permission != *self.permission.key {
+            return Err(error!(SwitchboardError::PermissionMismatch));
+        }
+
+        // the program only transfers what the escrow is short of the request cost
+        let cost = queue
+            .vrf_request_cost(vrf.batch_size)?
+            .saturating_sub(self.escrow.amount);
+        if self.payer_wallet.amount < cost {
+            msg!("payer wallet needs {} tokens", cost);
+            return Err(error!(SwitchboardError::VrfInsufficientFundsError));
+        }
+        Ok(())
+    }
+
     pub fn get_instruction(
         &self,
         program_id: Pubkey,
         params: VrfRequestRandomnessParams,
     ) -> anchor_lang::Result<Instruction> {
-        let accounts = self.to_account_metas(None);
-
-        let mut data: Vec<u8> = VrfRequestRandomness::discriminator().try_to_vec()?;
-        let mut param_vec: Vec<u8> = params.try_to_vec()?;
-        data.append(&mut param_vec);
-
-        let instruction = Instruction::new_with_bytes(program_id, &data, accounts);
-        Ok(instruction)
+        build_instruction(
+            program_id,
+            VrfRequestRandomness::discriminator(),
+            params,
+            self.to_account_metas(None),
+        )
     }
 
     pub fn invoke(
@@ -473,7 +838,8 @@ impl<'info> VrfRequestRandomness<'info> {
         let instruction = self.get_instruction(program.key.clone(), cpi_params)?;
         let account_infos = self.to_account_infos();
 
-        invoke(&instruction, &account_infos[..]).map_err(|_| error!(SwitchboardError::VrfCpiError))
+        invoke_instruction(&instruction, &account_infos, None)
+            .map_err(|_| error!(SwitchboardError::VrfCpiError))
     }
 
     pub fn invoke_signed(
@@ -490,7 +856,7 @@ impl<'info> VrfRequestRandomness<'info> {
         let instruction = self.get_instruction(program.key.clone(), cpi_params)?;
         let account_infos = self.to_account_infos();
 
-        invoke_signed(&instruction, &account_infos[..], signer_seeds)
+        invoke_instruction(&instruction, &account_infos, Some(signer_seeds))
             .map_err(|_| error!(SwitchboardError::VrfCpiSignedError))
     }
 
@@ -578,6 +944,201 @@ impl<'info> VrfRequestRandomness<'info> {
     }
 }
 
+#[derive(Accounts)]
+#[instruction(params: VrfInitParams)] // rpc parameters hint
+pub struct VrfInit<'info> {
+    #[account(mut)]
+    pub vrf: AccountInfo<'info>,
+    pub authority: AccountInfo<'info>,
+    pub oracle_queue: AccountInfo<'info>,
+    #[account(mut)]
+    pub escrow: Account<'info, TokenAccount>,
+    #[account(seeds = [b"STATE"], bump = params.state_bump)]
+    pub program_state: AccountInfo<'info>,
+    pub token_program: AccountInfo<'info>,
+}
+
+#[derive(Clone, Debug, AnchorSerialize, AnchorDeserialize)]
+pub struct VrfInitParams {
+    pub callback: Callback,
+    pub state_bump: u8,
+}
+
+impl<'info> VrfInit<'info> {
+    pub(crate) fn discriminator() -> [u8; 8] {
+        [241, 76, 92, 234, 230, 240, 164, 0]
+    }
+
+    pub fn get_instruction(
+        &self,
+        program_id: Pubkey,
+        params: VrfInitParams,
+    ) -> anchor_lang::Result<Instruction> {
+        build_instruction(
+            program_id,
+            VrfInit::discriminator(),
+            params,
+            self.to_account_metas(None),
+        )
+    }
+
+    pub fn invoke(
+        &self,
+        program: AccountInfo<'info>,
+        callback: Callback,
+        state_bump: u8,
+    ) -> anchor_lang::Result<()> {
+        let cpi_params = VrfInitParams {
+            callback,
+            state_bump,
+        };
+        let instruction = self.get_instruction(*program.key, cpi_params)?;
+        let account_infos = self.to_account_infos();
+
+        invoke_instruction(&instruction, &account_infos, None)
+    }
+
+    pub fn invoke_signed(
+        &self,
+        program: AccountInfo<'info>,
+        callback: Callback,
+        state_bump: u8,
+        signer_seeds: &[&[&[u8]]],
+    ) -> anchor_lang::Result<()> {
+        let cpi_params = VrfInitParams {
+            callback,
+            state_bump,
+        };
+        let instruction = self.get_instruction(*program.key, cpi_params)?;
+        let account_infos = self.to_account_infos();
+
+        invoke_instruction(&instruction, &account_infos, Some(signer_seeds))
+    }
+
+    fn to_account_infos(&self) -> Vec<AccountInfo<'info>> {
+        vec![
+            self.vrf.clone(),
+            self.authority.clone(),
+            self.oracle_queue.clone(),
+            self.escrow.to_account_info(),
+            self.program_state.clone(),
+            self.token_program.clone(),
+        ]
+    }
+
+    #[allow(unused_variables)]
+    fn to_account_metas(&self, is_signer: Option<bool>) -> Vec<AccountMeta> {
+        vec![
+            AccountMeta {
+                pubkey: *self.vrf.key,
+                is_signer: self.vrf.is_signer,
+                is_writable: self.vrf.is_writable,
+            },
+            AccountMeta {
+                pubkey: *self.authority.key,
+                is_signer: self.authority.is_signer,
+                is_writable: self.authority.is_writable,
+            },
+            AccountMeta {
+                pubkey: *self.oracle_queue.key,
+                is_signer: self.oracle_queue.is_signer,
+                is_writable: self.oracle_queue.is_writable,
+            },
+            AccountMeta {
+                pubkey: self.escrow.key(),
+                is_signer: self.escrow.to_account_info().is_signer,
+                is_writable: self.escrow.to_account_info().is_writable,
+            },
+            AccountMeta {
+                pubkey: *self.program_state.key,
+                is_signer: self.program_state.is_signer,
+                is_writable: self.program_state.is_writable,
+            },
+            AccountMeta {
+                pubkey: *self.token_program.key,
+                is_signer: self.token_program.is_signer,
+                is_writable: self.token_program.is_writable,
+            },
+        ]
+    }
+}
+
+#[derive(Accounts)]
+#[instruction(params: VrfSetCallbackParams)] // rpc parameters hint
+pub struct VrfSetCallback<'info> {
+    #[account(mut)]
+    pub vrf: AccountInfo<'info>,
+    #[account(signer)]
+    pub authority: AccountInfo<'info>,
+}
+
+#[derive(Clone, Debug, AnchorSerialize, AnchorDeserialize)]
+pub struct VrfSetCallbackParams {
+    pub callback: Callback,
+}
+
+impl<'info> VrfSetCallback<'info> {
+    pub(crate) fn discriminator() -> [u8; 8] {
+        [121, 167, 168, 191, 180, 247, 251, 78]
+    }
+
+    pub fn get_instruction(
+        &self,
+        program_id: Pubkey,
+        params: VrfSetCallbackParams,
+    ) -> anchor_lang::Result<Instruction> {
+        build_instruction(
+            program_id,
+            VrfSetCallback::discriminator(),
+            params,
+            self.to_account_metas(None),
+        )
+    }
+
+    pub fn invoke(
+        &self,
+        program: AccountInfo<'info>,
+        callback: Callback,
+    ) -> anchor_lang::Result<()> {
+        let instruction = self.get_instruction(*program.key, VrfSetCallbackParams { callback })?;
+        let account_infos = self.to_account_infos();
+
+        invoke_instruction(&instruction, &account_infos, None)
+    }
+
+    pub fn invoke_signed(
+        &self,
+        program: AccountInfo<'info>,
+        callback: Callback,
+        signer_seeds: &[&[&[u8]]],
+    ) -> anchor_lang::Result<()> {
+        let instruction = self.get_instruction(*program.key, VrfSetCallbackParams { callback })?;
+        let account_infos = self.to_account_infos();
+
+        invoke_instruction(&instruction, &account_infos, Some(signer_seeds))
+    }
+
+    fn to_account_infos(&self) -> Vec<AccountInfo<'info>> {
+        vec![self.vrf.clone(), self.authority.clone()]
+    }
+
+    #[allow(unused_variables)]
+    fn to_account_metas(&self, is_signer: Option<bool>) -> Vec<AccountMeta> {
+        vec![
+            AccountMeta {
+                pubkey: *self.vrf.key,
+                is_signer: self.vrf.is_signer,
+                is_writable: self.vrf.is_writable,
+            },
+            AccountMeta {
+                pubkey: *self.authority.key,
+                is_signer: true, // overwrite, authority has to sign
+                is_writable: self.authority.is_writable,
+            },
+        ]
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -1626,6 +2187,484 @@ mod tests {
         );
         let vrf = VrfAccountData::new(&vrf_account_info).unwrap();
         assert_eq!(vrf.get_result().unwrap(), VRF_RESULT);
-        assert_eq!(vrf.status, VrfStatus::StatusCallbackSuccess);
+        assert_eq!(vrf.get_status(), VrfStatus::StatusCallbackSuccess);
+    }
+
+    #[test]
+    fn test_vrf_set_callback_instruction() {
+        let authority = Pubkey::new_unique();
+        let (mut vrf_lamports, mut authority_lamports) = (0, 0);
+        let (mut vrf_data, mut authority_data) = (vec![], vec![]);
+        let accounts = VrfSetCallback {
+            vrf: AccountInfo::new(
+                &VRF_PUBKEY,
+                false,
+                true,
+                &mut vrf_lamports,
+                &mut vrf_data,
+                &SWITCHBOARD_V2_DEVNET,
+                false,
+                0,
+            ),
+            authority: AccountInfo::new(
+                &authority,
+                false,
+                false,
+                &mut authority_lamports,
+                &mut authority_data,
+                &authority,
+                false,
+                0,
+            ),
+        };
+        let callback = Callback {
+            program_id: authority,
+            accounts: vec![],
+            ix_data: vec![1, 2, 3],
+        };
+        let ix = accounts
+            .get_instruction(
+                SWITCHBOARD_V2_DEVNET,
+                VrfSetCallbackParams {
+                    callback: callback.clone(),
+                },
+            )
+            .unwrap();
+        assert_eq!(ix.program_id, SWITCHBOARD_V2_DEVNET);
+        assert_eq!(
+            ix.accounts,
+            vec![
+                AccountMeta::new(VRF_PUBKEY, false),
+                AccountMeta::new_readonly(authority, true),
+            ]
+        );
+        assert_eq!(ix.data[..8], [121, 167, 168, 191, 180, 247, 251, 78]);
+        assert_eq!(ix.data[8..], callback.try_to_vec().unwrap()[..]);
+    }
+
+    fn token_account_data(owner: &Pubkey, amount: u64) -> Vec<u8> {
+        use anchor_spl::token::spl_token::state::{Account as SplAccount, AccountState};
+        use solana_program::program_pack::Pack;
+        let mut data = vec![0u8; SplAccount::LEN];
+        SplAccount {
+            owner: *owner,
+            amount,
+            state: AccountState::Initialized,
+            ..Default::default()
+        }
+        .pack_into_slice(&mut data);
+        data
+    }
+
+    fn set_token_account(account: &mut Account<TokenAccount>, owner: &Pubkey, amount: u64) {
+        account
+            .to_account_info()
+            .try_borrow_mut_data()
+            .unwrap()
+            .copy_from_slice(&token_account_data(owner, amount));
+        account.reload().unwrap();
+    }
+
+    #[test]
+    fn test_vrf_request_validate() {
+        let vrf_state: VrfAccountData = *bytemuck::from_bytes(&VRF_ACCOUNT_DATA[8..]);
+        let queue_key = vrf_state.oracle_queue;
+        let escrow_key = vrf_state.escrow;
+        let queue_authority = Pubkey::new_unique();
+        let data_buffer = Pubkey::new_unique();
+        let (program_state, state_bump) =
+            Pubkey::find_program_address(&[b"STATE"], &SWITCHBOARD_V2_DEVNET);
+        let (permission, permission_bump) = Pubkey::find_program_address(
+            &[
+                b"PermissionAccountData",
+                queue_authority.as_ref(),
+                queue_key.as_ref(),
+                VRF_PUBKEY.as_ref(),
+            ],
+            &SWITCHBOARD_V2_DEVNET,
+        );
+
+        let mut queue = crate::OracleQueueAccountData::zeroed();
+        queue.authority = queue_authority;
+        queue.data_buffer = data_buffer;
+        queue.reward = 100;
+        let mut queue_data = [164, 207, 200, 51, 199, 113, 35, 109].to_vec();
+        queue_data.extend_from_slice(bytemuck::bytes_of(&queue));
+
+        let token_program = anchor_spl::token::ID;
+        let payer = Pubkey::new_unique();
+        let mut vrf_data = VRF_ACCOUNT_DATA.clone();
+        let mut escrow_data = token_account_data(&program_state, 30);
+        let mut payer_data = token_account_data(&payer, 70);
+        let mut lamports = [0u64; 12];
+        let mut empty: [Vec<u8>; 6] = Default::default();
+        let [l0, l1, l2, l3, l4, l5, l6, l7, l8, l9, l10, l11] = &mut lamports;
+        let [e0, e1, e2, e3, e4, e5] = &mut empty;
+        let info = |key, lamports, data, owner| {
+            AccountInfo::new(key, false, true, lamports, data, owner, false, 0)
+        };
+        let escrow_info = info(&escrow_key, l6, &mut escrow_data, &token_program);
+        let payer_info = info(&payer, l7, &mut payer_data, &token_program);
+        let mut accounts = VrfRequestRandomness {
+            authority: info(&payer, l0, e0, &payer),
+            vrf: info(&VRF_PUBKEY, l1, &mut vrf_data, &SWITCHBOARD_V2_DEVNET),
+            oracle_queue: info(&queue_key, l2, &mut queue_data, &SWITCHBOARD_V2_DEVNET),
+            queue_authority: info(&queue_authority, l3, e1, &queue_authority),
+            data_buffer: info(&data_buffer, l4, e2, &SWITCHBOARD_V2_DEVNET),
+            permission: info(&permission, l5, e3, &SWITCHBOARD_V2_DEVNET),
+            escrow: Account::try_from(&escrow_info).unwrap(),
+            payer_wallet: Account::try_from(&payer_info).unwrap(),
+            payer_authority: info(&payer, l8, e4, &payer),
+            recent_blockhashes: info(&payer, l9, e5, &payer),
+            program_state: info(&program_state, l10, &mut [], &SWITCHBOARD_V2_DEVNET),
+            token_program: info(&token_program, l11, &mut [], &token_program),
+        };
+        let params = VrfRequestRandomnessParams {
+            permission_bump,
+            state_bump,
+        };
+        let validate = |accounts: &VrfRequestRandomness, params: &VrfRequestRandomnessParams| {
+            accounts
+                .validate(&SWITCHBOARD_V2_DEVNET, params)
+                .map_err(|err| match err {
+                    anchor_lang::error::Error::AnchorError(err) => err.error_name,
+                    err => err.to_string(),
+                })
+        };
+
+        assert_eq!(validate(&accounts, &params), Ok(()));
+
+        let mut wrong_bump = params.clone();
+        wrong_bump.permission_bump = wrong_bump.permission_bump.wrapping_sub(1);
+        assert_eq!(
+            validate(&accounts, &wrong_bump).unwrap_err(),
+            "PermissionMismatch"
+        );
+
+        set_token_account(&mut accounts.payer_wallet, &payer, 69);
+        assert_eq!(
+            validate(&accounts, &params).unwrap_err(),
+            "VrfInsufficientFundsError"
+        );
+        set_token_account(&mut accounts.payer_wallet, &payer, 70);
+
+        set_token_account(&mut accounts.escrow, &payer, 30);
+        assert_eq!(
+            validate(&accounts, &params).unwrap_err(),
+            "VrfEscrowMismatch"
+        );
+        set_token_account(&mut accounts.escrow, &program_state, 30);
+
+        accounts.data_buffer = accounts.queue_authority.clone();
+        assert_eq!(
+            validate(&accounts, &params).unwrap_err(),
+            "VrfQueueMismatch"
+        );
+        accounts.data_buffer = accounts.permission.clone();
+        accounts.vrf.try_borrow_mut_data().unwrap()[8] = VrfStatus::StatusRequesting.into();
+        assert_eq!(
+            validate(&accounts, &params).unwrap_err(),
+            "VrfRequestInProgressError"
+        );
+    }
+
+    #[test]
+    fn test_vrf_size() {
+        assert_eq!(VrfAccountData::size(), VRF_ACCOUNT_DATA.len());
+    }
+
+    #[test]
+    fn test_vrf_unknown_bytes() {
+        // status and bool bytes newer program versions could write
+        let mut vrf_data = VRF_ACCOUNT_DATA.clone();
+        vrf_data[8] = 9;
+        let builder_offset = 8 + 2269;
+        vrf_data[builder_offset + 32] = 200;
+        vrf_data[builder_offset + 3115] = 2;
+        let mut lamports = 0;
+        let vrf_account_info = AccountInfo::new(
+            &VRF_PUBKEY,
+            false,
+            false,
+            &mut lamports,
+            &mut vrf_data,
+            &SWITCHBOARD_V2_DEVNET,
+            false,
+            0,
+        );
+        let vrf = VrfAccountData::new(&vrf_account_info).unwrap();
+        assert_eq!(vrf.get_status(), VrfStatus::Unknown(9));
+        assert_eq!(vrf.builders[0].get_status(), VrfStatus::Unknown(200));
+        assert!(vrf.builders[0].is_verified());
+        assert!(!vrf.is_test_mode());
+
+        for status in 0..=u8::MAX {
+            assert_eq!(u8::from(VrfStatus::from(status)), status);
+        }
+    }
+
+    #[test]
+    fn test_vrf_callback() {
+        let discriminator = [1u8, 2, 3, 4, 5, 6, 7, 8];
+        let accounts = vec![
+            AccountMeta::new(VRF_PUBKEY, false),
+            AccountMeta::new_readonly(SWITCHBOARD_V2_DEVNET, true),
+        ];
+        let mut ix_data = discriminator.to_vec();
+        ix_data.push(9);
+        let callback = Callback {
+            program_id: SWITCHBOARD_V2_MAINNET,
+            accounts: accounts
+                .iter()
+                .map(|meta| AccountMetaBorsh {
+                    pubkey: meta.pubkey,
+                    is_signer: meta.is_signer,
+                    is_writable: meta.is_writable,
+                })
+                .collect(),
+            ix_data,
+        };
+
+        let callback_zc: CallbackZC = (&callback).try_into().unwrap();
+        let round_trip: Callback = (&callback_zc).try_into().unwrap();
+        assert_eq!(
+            round_trip.try_to_vec().unwrap(),
+            callback.try_to_vec().unwrap()
+        );
+
+        assert!(callback_zc
+            .validate(&SWITCHBOARD_V2_MAINNET, &discriminator, &accounts)
+            .is_ok());
+        assert!(callback_zc
+            .validate(&SWITCHBOARD_V2_DEVNET, &discriminator, &accounts)
+            .is_err());
+        assert!(callback_zc
+            .validate(&SWITCHBOARD_V2_MAINNET, &[0u8; 8], &accounts)
+            .is_err());
+        assert!(callback_zc
+            .validate(&SWITCHBOARD_V2_MAINNET, &discriminator, &accounts[..1])
+            .is_err());
+        let mut readonly = accounts.clone();
+        readonly[0].is_writable = false;
+        assert!(callback_zc
+            .validate(&SWITCHBOARD_V2_MAINNET, &discriminator, &readonly)
+            .is_err());
+
+        let mut oversized = callback_zc;
+        oversized.accounts_len = 33;
+        assert!(Callback::try_from(&oversized).is_err());
+        let too_large = Callback {
+            program_id: SWITCHBOARD_V2_MAINNET,
+            accounts: vec![],
+            ix_data: vec![0u8; 1025],
+        };
+        assert!(CallbackZC::try_from(&too_large).is_err());
+    }
+
+    // instructions sysvar data for a transaction with a single instruction over the given accounts
+    fn instructions_sysvar_data(program_id: &Pubkey, accounts: &[Pubkey], ix: &[u8]) -> Vec<u8> {
+        let mut data = 1u16.to_le_bytes().to_vec();
+        data.extend_from_slice(&4u16.to_le_bytes());
+        data.extend_from_slice(&(accounts.len() as u16).to_le_bytes());
+        for account in accounts.iter() {
+            data.push(0b10); // writable
+            data.extend_from_slice(account.as_ref());
+        }
+        data.extend_from_slice(program_id.as_ref());
+        data.extend_from_slice(&(ix.len() as u16).to_le_bytes());
+        data.extend_from_slice(ix);
+        data.extend_from_slice(&0u16.to_le_bytes()); // current instruction index
+        data
+    }
+
+    #[test]
+    fn test_vrf_authenticate_callback() {
+        let instructions_id = solana_program::sysvar::instructions::id();
+        let prove_and_verify = VRF_CALLBACK_INSTRUCTIONS[0].0;
+        let verify = VRF_CALLBACK_INSTRUCTIONS[1].0;
+        let authenticate = |vrf_data: &mut [u8],
+                            ix_program: &Pubkey,
+                            ix_accounts: &[Pubkey],
+                            ix: &[u8],
+                            last_counter: u128| {
+            let mut ix_data = instructions_sysvar_data(ix_program, ix_accounts, ix);
+            let mut ix_lamports = 0;
+            let ix_info = AccountInfo::new(
+                &instructions_id,
+                false,
+                false,
+                &mut ix_lamports,
+                &mut ix_data,
+                &instructions_id,
+                false,
+                0,
+            );
+            let mut lamports = 0;
+            let vrf_account_info = AccountInfo::new(
+                &VRF_PUBKEY,
+                false,
+                false,
+                &mut lamports,
+                vrf_data,
+                &SWITCHBOARD_V2_DEVNET,
+                false,
+                0,
+            );
+            VrfAccountData::authenticate_callback(
+                &vrf_account_info,
+                &ix_info,
+                &SWITCHBOARD_V2_DEVNET,
+                last_counter,
+            )
+            .map(|vrf| vrf.get_result().unwrap())
+        };
+        let accounts = [VRF_PUBKEY, Pubkey::default()];
+
+        let mut vrf_data = VRF_ACCOUNT_DATA.clone();
+        // the fixture is past the callback, roll it back to verified
+        vrf_data[8] = VrfStatus::StatusVerified.into();
+        for ix in [prove_and_verify, verify].iter() {
+            assert_eq!(
+                authenticate(&mut vrf_data, &SWITCHBOARD_V2_DEVNET, &accounts, ix, 13).unwrap(),
+                VRF_RESULT
+            );
+        }
+        // the counter has to move past the last round the caller saw
+        assert_eq!(
+            authenticate(
+                &mut vrf_data,
+                &SWITCHBOARD_V2_DEVNET,
+                &accounts,
+                &verify,
+                14
+            )
+            .unwrap_err(),
+            error!(SwitchboardError::VrfStaleRoundError)
+        );
+
+        let unauthorized = [
+            // not the switchboard program
+            (VRF_PUBKEY, accounts, verify.to_vec()),
+            // the vrf account is not at the vrf index
+            (
+                SWITCHBOARD_V2_DEVNET,
+                [Pubkey::default(), VRF_PUBKEY],
+                verify.to_vec(),
+            ),
+            // some other switchboard instruction
+            (
+                SWITCHBOARD_V2_DEVNET,
+                accounts,
+                VrfRequestRandomness::discriminator().to_vec(),
+            ),
+            (SWITCHBOARD_V2_DEVNET, accounts, vec![]),
+        ];
+        for (ix_program, ix_accounts, ix) in unauthorized.iter() {
+            assert_eq!(
+                authenticate(&mut vrf_data, ix_program, ix_accounts, ix, 13).unwrap_err(),
+                error!(SwitchboardError::VrfCallbackUnauthorized)
+            );
+        }
+
+        let mut vrf_data = VRF_ACCOUNT_DATA.clone();
+        assert_eq!(
+            authenticate(
+                &mut vrf_data,
+                &SWITCHBOARD_V2_DEVNET,
+                &accounts,
+                &verify,
+                13
+            )
+            .unwrap_err(),
+            error!(SwitchboardError::VrfNotVerifiedError)
+        );
+    }
+
+    #[test]
+    fn test_vrf_lifecycle() {
+        let mut vrf_data = VRF_ACCOUNT_DATA.clone();
+        let mut lamports = 0;
+        let vrf_account_info = AccountInfo::new(
+            &VRF_PUBKEY,
+            false,
+            false,
+            &mut lamports,
+            &mut vrf_data,
+            &SWITCHBOARD_V2_DEVNET,
+            false,
+            0,
+        );
+        let mut vrf = *VrfAccountData::new(&vrf_account_info).unwrap();
+        assert!(vrf.is_verified());
+        assert!(vrf.callback_succeeded());
+        assert!(!vrf.is_pending());
+        assert!(!vrf.verify_failed());
+
+        let request_slot = vrf.current_round.request_slot;
+        let request_timestamp = vrf.current_round.request_timestamp;
+        let counter = vrf.counter;
+        assert_eq!(
+            vrf.get_fresh_result(request_slot, request_timestamp, counter)
+                .unwrap(),
+            VRF_RESULT
+        );
+        assert!(vrf
+            .check_round_freshness(request_slot + 1, request_timestamp, counter)
+            .is_err());
+        assert!(vrf
+            .check_round_freshness(request_slot, request_timestamp + 1, counter)
+            .is_err());
+        assert!(vrf
+            .check_round_freshness(request_slot, request_timestamp, counter + 1)
+            .is_err());
+
+        vrf.status = VrfStatus::StatusVerifying.into();
+        assert!(vrf.is_pending());
+        assert!(vrf
+            .get_fresh_result(request_slot, request_timestamp, counter)
+            .is_err());
+        vrf.status = VrfStatus::StatusVerifyFailure.into();
+        assert!(vrf.verify_failed());
+        assert!(!vrf.is_verified());
+    }
+
+    #[cfg(feature = "ecvrf")]
+    #[test]
+    fn test_vrf_verify_current_round() {
+        let mut vrf_data = VRF_ACCOUNT_DATA.clone();
+        let mut lamports = 0;
+        let vrf_account_info = AccountInfo::new(
+            &VRF_PUBKEY,
+            false,
+            false,
+            &mut lamports,
+            &mut vrf_data,
+            &SWITCHBOARD_V2_DEVNET,
+            false,
+            0,
+        );
+        let vrf = *VrfAccountData::new(&vrf_account_info).unwrap();
+        assert_eq!(vrf.verify_current_round().unwrap(), VRF_RESULT);
+        assert_eq!(vrf.builders[0].output().unwrap(), VRF_RESULT);
+
+        let mut tampered = vrf;
+        tampered.current_round.alpha[0] ^= 1;
+        assert!(tampered.verify_current_round().is_err());
+
+        // a valid proof does not vouch for result bytes it did not produce
+        let mut tampered = vrf;
+        tampered.current_round.result[0] ^= 1;
+        assert!(tampered.verify_current_round().is_err());
+        let mut tampered = vrf;
+        tampered.builders[0].result[0] ^= 1;
+        assert!(tampered.verify_current_round().is_err());
+
+        // the stored gamma has to be the point the proof commits to
+        let mut tampered = vrf;
+        let mut x = tampered.builders[0].proof.Gamma.X;
+        x.bytes[0] ^= 1;
+        tampered.builders[0].proof.Gamma.X = x;
+        assert!(tampered.builders[0].output().is_err());
+        assert!(tampered.verify_current_round().is_err());
     }
 }
@@ -0,0 +1,400 @@
+//! Unbiased sampling from a VRF result.
+//!
+//! Reducing the raw result with `result % n` favours small values whenever `n` does not divide
+//! 2^256. These helpers instead read the result as a stream of little endian u64 words and reject
+//! words that fall in the biased tail, so every value in a range is exactly equally likely. The
+//! first 32 bytes of the stream are the result itself; further blocks are expanded with
+//! `sha256(result || block_index_le_u64)`, which is cheap on-chain through the hash syscall and
+//! reproducible off-chain. Use `VrfRng` to draw many values from a single round, and `shuffle`
+//! or `weighted_index` for raffles and loot tables.
+use super::error::SwitchboardError;
+use super::vrf::VrfAccountData;
+use anchor_lang::prelude::*;
+use solana_program::hash::hashv;
+use std::convert::TryInto;
+
+/// Uniform integer in `[lo, hi)` derived from a VRF result.
+pub fn random_range(result: &[u8; 32], lo: u64, hi: u64) -> anchor_lang::Result<u64> {
+    VrfRng::new(*result).gen_range(lo, hi)
+}
+
+/// `true` with probability exactly `numerator / denominator`.
+pub fn random_bool(
+    result: &[u8; 32],
+    numerator: u64,
+    denominator: u64,
+) -> anchor_lang::Result<bool> {
+    VrfRng::new(*result).gen_bool(numerator, denominator)
+}
+
+/// Uniform float in `[0, 1)` with 53 bits of precision, every representable multiple of 2^-53
+/// being equally likely.
+pub fn random_f64(result: &[u8; 32]) -> f64 {
+    VrfRng::new(*result).gen_f64()
+}
+
+/// Shuffle `items` in place with the stream seeded from a VRF result, see `VrfRng::shuffle`.
+pub fn shuffle<T>(result: &[u8; 32], items: &mut [T]) {
+    VrfRng::new(*result).shuffle(items)
+}
+
+/// Draw an index with probability proportional to `weights[idx]`. Zero weights are never drawn.
+pub fn weighted_index(result: &[u8; 32], weights: &[u64]) -> anchor_lang::Result<usize> {
+    VrfRng::new(*result).weighted_index(weights)
+}
+
+impl VrfAccountData {
+    /// current round result as a uniform integer in `[lo, hi)`, see `vrf_random::random_range`
+    pub fn get_result_range(&self, lo: u64, hi: u64) -> anchor_lang::Result<u64> {
+        random_range(&self.get_result()?, lo, hi)
+    }
+
+    /// current round result as a bool that is `true` with probability `numerator / denominator`
+    pub fn get_result_bool(&self, numerator: u64, denominator: u64) -> anchor_lang::Result<bool> {
+        random_bool(&self.get_result()?, numerator, denominator)
+    }
+
+    /// current round result as a uniform float in `[0, 1)`
+    pub fn get_result_f64(&self) -> anchor_lang::Result<f64> {
+        Ok(random_f64(&self.get_result()?))
+    }
+
+    /// random stream seeded from the current round result and request counter
+    pub fn rng(&self) -> anchor_lang::Result<VrfRng> {
+        Ok(VrfRng::with_counter(self.get_result()?, self.counter))
+    }
+}
+
+/// Deterministic random stream seeded from a VRF result, for drawing many values from one round.
+///
+/// The stream is the seed followed by `sha256(seed || block_index_le_u64)` blocks, so anyone with
+/// the round result can reproduce every value drawn from it. `VrfRng::new` yields the same words
+/// as the free functions in this module; `VrfRng::with_counter` additionally mixes in the request
+/// counter so consecutive rounds never share a stream.
+#[derive(Clone, Debug)]
+pub struct VrfRng {
+    seed: [u8; 32],
+    block: [u8; 32],
+    block_idx: u64,
+    word_idx: usize,
+}
+
+impl VrfRng {
+    pub fn new(result: [u8; 32]) -> Self {
+        Self {
+            seed: result,
+            block: result,
+            block_idx: 0,
+            word_idx: 0,
+        }
+    }
+
+    /// seed with `sha256(result || counter_le_u128)`
+    pub fn with_counter(result: [u8; 32], counter: u128) -> Self {
+        Self::new(hashv(&[&result, &counter.to_le_bytes()]).to_bytes())
+    }
+
+    pub fn next_u64(&mut self) -> u64 {
+        if self.word_idx == 4 {
+            self.block_idx += 1;
+            self.block = hashv(&[&self.seed, &self.block_idx.to_le_bytes()]).to_bytes();
+            self.word_idx = 0;
+        }
+        let start = self.word_idx * 8;
+        self.word_idx += 1;
+        u64::from_le_bytes(self.block[start..start + 8].try_into().unwrap())
+    }
+
+    /// fill `dest` with little endian words from the stream, discarding the unused tail of the
+    /// last word
+    pub fn fill_bytes(&mut self, dest: &mut [u8]) {
+        for chunk in dest.chunks_mut(8) {
+            let word = self.next_u64().to_le_bytes();
+            chunk.copy_from_slice(&word[..chunk.len()]);
+        }
+    }
+
+    /// uniform integer in `[lo, hi)`
+    pub fn gen_range(&mut self, lo: u64, hi: u64) -> anchor_lang::Result<u64> {
+        if lo >= hi {
+            return Err(error!(SwitchboardError::InvalidRandomRange));
+        }
+        Ok(lo + self.next_below(hi - lo))
+    }
+
+    /// `true` with probability exactly `numerator / denominator`
+    pub fn gen_bool(&mut self, numerator: u64, denominator: u64) -> anchor_lang::Result<bool> {
+        if denominator == 0 || numerator > denominator {
+            return Err(error!(SwitchboardError::InvalidRandomProbability));
+        }
+        Ok(self.next_below(denominator) < numerator)
+    }
+
+    /// uniform float in `[0, 1)` with 53 bits of precision
+    pub fn gen_f64(&mut self) -> f64 {
+        (self.next_u64() >> 11) as f64 * (1.0 / (1u64 << 53) as f64)
+    }
+
+    /// Fisher-Yates shuffle `items` in place, drawing one index per position from the back
+    pub fn shuffle<T>(&mut self, items: &mut [T]) {
+        for i in (1..items.len()).rev() {
+            let j = self.next_below(i as u64 + 1) as usize;
+            items.swap(i, j);
+        }
+    }
+
+    /// index of an entry drawn with probability proportional to its weight
+    pub fn weighted_index(&mut self, weights: &[u64]) -> anchor_lang::Result<usize> {
+        let total = weights
+            .iter()
+            .try_fold(0u64, |total, weight| total.checked_add(*weight))
+            .ok_or(error!(SwitchboardError::IntegerOverflowError))?;
+        if total == 0 {
+            return Err(error!(SwitchboardError::InvalidRandomWeights));
+        }
+        let mut target = self.next_below(total);
+        for (idx, weight) in weights.iter().enumerate() {
+            if target < *weight {
+                return Ok(idx);
+            }
+            target -= weight;
+        }
+        unreachable!()
+    }
+
+    // Reject words below 2^64 mod range so the accepted words split evenly across the range.
+    fn next_below(&mut self, range: u64) -> u64 {
+        let threshold = range.wrapping_neg() % range;
+        loop {
+            let word = self.next_u64();
+            if word >= threshold {
+                return word % range;
+            }
+        }
+    }
+}
+
+#[cfg(feature = "rand")]
+impl rand_core::RngCore for VrfRng {
+    fn next_u32(&mut self) -> u32 {
+        self.next_u64() as u32
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        VrfRng::next_u64(self)
+    }
+
+    fn fill_bytes(&mut self, dest: &mut [u8]) {
+        VrfRng::fill_bytes(self, dest)
+    }
+
+    fn try_fill_bytes(&mut self, dest: &mut [u8]) -> std::result::Result<(), rand_core::Error> {
+        VrfRng::fill_bytes(self, dest);
+        Ok(())
+    }
+}
+
+#[cfg(feature = "rand")]
+impl rand_core::CryptoRng for VrfRng {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // reference outputs for front-ends reimplementing the stream
+    const SHUFFLE_VECTOR: [u32; 10] = [8, 0, 4, 9, 7, 6, 5, 1, 2, 3];
+    const WEIGHTED_VECTOR: [usize; 8] = [2, 2, 3, 3, 2, 3, 3, 2];
+
+    fn seed(i: u64) -> [u8; 32] {
+        hashv(&[b"seed", &i.to_le_bytes()]).to_bytes()
+    }
+
+    #[test]
+    fn test_random_range_distribution() {
+        let samples = 30_000u64;
+        let mut counts = [0u64; 3];
+        for i in 0..samples {
+            let value = random_range(&seed(i), 10, 13).unwrap();
+            counts[(value - 10) as usize] += 1;
+        }
+        // chi-squared with 2 degrees of freedom, 13.8 is the 0.1% critical value
+        let expected = samples as f64 / 3.0;
+        let chi_squared: f64 = counts
+            .iter()
+            .map(|count| (*count as f64 - expected).powi(2) / expected)
+            .sum();
+        assert!(chi_squared < 13.8, "{:?}", counts);
+    }
+
+    #[test]
+    fn test_random_range_rejects_biased_words() {
+        // nearly half of all words fall in the biased tail for this range, so a plain modulo
+        // would return values below 2^63 - 1 twice as often as values above it
+        let range = (1u64 << 63) + 1;
+        let samples = 10_000u64;
+        let mut low = 0u64;
+        for i in 0..samples {
+            let value = random_range(&seed(i), 0, range).unwrap();
+            assert!(value < range);
+            if value < range / 2 {
+                low += 1;
+            }
+        }
+        assert!((4_700..5_300).contains(&low), "{}", low);
+
+        // a result of all zero words is rejected until the expanded blocks supply a valid word
+        let value = random_range(&[0u8; 32], 0, range).unwrap();
+        assert!(value < range);
+        assert_eq!(random_range(&[7u8; 32], 5, 6).unwrap(), 5);
+        assert!(random_range(&seed(0), 6, 6).is_err());
+    }
+
+    #[test]
+    fn test_random_bool() {
+        let samples = 20_000u64;
+        let hits = (0..samples)
+            .filter(|i| random_bool(&seed(*i), 1, 4).unwrap())
+            .count();
+        assert!((4_700..5_300).contains(&hits), "{}", hits);
+
+        assert!(!random_bool(&seed(0), 0, 1).unwrap());
+        assert!(random_bool(&seed(0), 1, 1).unwrap());
+        assert!(random_bool(&seed(0), 2, 1).is_err());
+        assert!(random_bool(&seed(0), 0, 0).is_err());
+    }
+
+    #[test]
+    fn test_random_f64() {
+        let samples = 20_000u64;
+        let mut sum = 0.0;
+        for i in 0..samples {
+            let value = random_f64(&seed(i));
+            assert!((0.0..1.0).contains(&value));
+            sum += value;
+        }
+        let mean = sum / samples as f64;
+        assert!((mean - 0.5).abs() < 0.01, "{}", mean);
+
+        assert_eq!(random_f64(&[0u8; 32]), 0.0);
+        assert!(random_f64(&[0xff; 32]) < 1.0);
+    }
+
+    #[test]
+    fn test_vrf_rng_reference_vector() {
+        // sha256 counter-mode stream, reproducible with any sha256 implementation
+        let mut rng = VrfRng::with_counter([1u8; 32], 1);
+        let words: Vec<u64> = (0..6).map(|_| rng.next_u64()).collect();
+        assert_eq!(
+            words,
+            vec![
+                17721462481016663733,
+                17473157920573577375,
+                7367228750513924396,
+                10539653728009380791,
+                4497437729907525434,
+                12291951624572145699,
+            ]
+        );
+    }
+
+    #[test]
+    fn test_vrf_rng_streams() {
+        let result = seed(1);
+        let mut rng = VrfRng::new(result);
+        assert_eq!(
+            rng.clone().gen_range(0, 1000).unwrap(),
+            random_range(&result, 0, 1000).unwrap()
+        );
+        assert_eq!(rng.clone().gen_f64(), random_f64(&result));
+
+        let mut bytes = [0u8; 12];
+        rng.clone().fill_bytes(&mut bytes);
+        assert_eq!(bytes[..8], result[..8]);
+        assert_eq!(bytes[8..], result[8..12]);
+
+        let mut counted = VrfRng::with_counter(result, 1);
+        let mut next_round = VrfRng::with_counter(result, 2);
+        let first = rng.next_u64();
+        assert_ne!(first, counted.next_u64());
+        assert_ne!(counted.next_u64(), next_round.next_u64());
+    }
+
+    #[cfg(feature = "rand")]
+    #[test]
+    fn test_vrf_rng_core() {
+        use rand_core::RngCore;
+
+        fn draw<R: RngCore + rand_core::CryptoRng>(rng: &mut R) -> u64 {
+            rng.next_u64()
+        }
+        let mut rng = VrfRng::with_counter(seed(2), 7);
+        let expected = rng.clone().next_u64();
+        assert_eq!(draw(&mut rng), expected);
+        let mut bytes = [0u8; 40];
+        assert!(rng.try_fill_bytes(&mut bytes).is_ok());
+    }
+
+    #[test]
+    fn test_shuffle_reference_vector() {
+        let mut items: [u32; 10] = [0, 1, 2, 3, 4, 5, 6, 7, 8, 9];
+        shuffle(&[1u8; 32], &mut items);
+        assert_eq!(items, SHUFFLE_VECTOR);
+
+        let mut empty: [u32; 0] = [];
+        shuffle(&[1u8; 32], &mut empty);
+        let mut single = [42u32];
+        shuffle(&[1u8; 32], &mut single);
+        assert_eq!(single, [42]);
+    }
+
+    #[test]
+    fn test_shuffle_distribution() {
+        // every item should land in every position equally often
+        let samples = 12_000u64;
+        let mut counts = [[0u64; 4]; 4];
+        for i in 0..samples {
+            let mut items = [0usize, 1, 2, 3];
+            shuffle(&seed(i), &mut items);
+            for (position, item) in items.iter().enumerate() {
+                counts[*item][position] += 1;
+            }
+        }
+        for row in counts.iter() {
+            for count in row.iter() {
+                assert!((2_800..3_200).contains(count), "{:?}", counts);
+            }
+        }
+
+        let mut large: Vec<u32> = (0..5_000).collect();
+        shuffle(&seed(0), &mut large);
+        large.sort_unstable();
+        assert!(large
+            .iter()
+            .enumerate()
+            .all(|(idx, item)| idx as u32 == *item));
+    }
+
+    #[test]
+    fn test_weighted_index() {
+        assert_eq!(
+            (0..8u8)
+                .map(|i| weighted_index(&[i; 32], &[1, 0, 3, 6]).unwrap())
+                .collect::<Vec<usize>>(),
+            WEIGHTED_VECTOR
+        );
+
+        let samples = 20_000u64;
+        let mut counts = [0u64; 4];
+        for i in 0..samples {
+            counts[weighted_index(&seed(i), &[1, 0, 3, 6]).unwrap()] += 1;
+        }
+        assert_eq!(counts[1], 0);
+        assert!((1_800..2_200).contains(&counts[0]), "{:?}", counts);
+        assert!((5_700..6_300).contains(&counts[2]), "{:?}", counts);
+        assert!((11_600..12_400).contains(&counts[3]), "{:?}", counts);
+
+        assert!(weighted_index(&seed(0), &[]).is_err());
+        assert!(weighted_index(&seed(0), &[0, 0]).is_err());
+        assert!(weighted_index(&seed(0), &[u64::MAX, 1]).is_err());
+    }
+}